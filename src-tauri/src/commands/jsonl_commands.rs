@@ -1,5 +1,5 @@
-use crate::jsonl::JsonlReaderManager;
-use crate::models::PagedResponse;
+use crate::jsonl::{JsonlReaderManager, SchemaExportOptions, FlattenOptions, ArrayMode};
+use crate::models::{PagedResponse, ExportSummary, IndexStatus, SortStatus, DedupSummary, DuplicateGroupPage, NearDupSummary, SchemaReport, FieldStatsReport, HistogramData, CategoryData, TimeSeriesData, ScatterData, ValidationSummary, ViolationPage, QualityReport, DiffSummary, DiffPage, RowRef, RowComparison, EditStatus, EditHistory, SaveSummary, DraftStatus, RebaseSummary, JsonObject, JsonValue};
 use tauri::{State, AppHandle, Emitter};
 use std::path::Path;
 
@@ -48,11 +48,12 @@ pub async fn set_jsonl_file(
 pub async fn load_page(
     page: usize,
     page_size: usize,
+    transform: Option<String>,
     manager: State<'_, JsonlReaderManager>
 ) -> Result<PagedResponse, String> {
     println!("加载页面数据: 页码={}, 每页行数={}", page, page_size);
     
-    let result = manager.load_page(page, page_size).await;
+    let result = manager.load_page(page, page_size, transform.as_deref()).await;
     
     match &result {
         Ok(response) => {
@@ -67,22 +68,70 @@ pub async fn load_page(
     result.map_err(|e| e.to_string())
 }
 
+/// 加载展开嵌套字段后的页面数据
+///
+/// 嵌套对象展开为 `address.city` 形式的列，`max_depth` 为最多展开的层数（默认 3）；
+/// `arrays` 为数组的展开方式：`index` 按下标展开（默认）、`join` 拼接标量数组、`keep` 不展开，
+/// `max_array_items` 为按下标展开时每个数组最多展开的元素数（默认 5）。
+#[tauri::command]
+pub async fn load_flat_page(
+    page: usize,
+    page_size: usize,
+    transform: Option<String>,
+    max_depth: Option<usize>,
+    arrays: Option<String>,
+    max_array_items: Option<usize>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<PagedResponse, String> {
+    println!("加载展开数据: 页码={}, 每页行数={}, 层数={:?}, 数组={:?}", page, page_size, max_depth, arrays);
+    
+    let defaults = FlattenOptions::default();
+    let options = FlattenOptions {
+        max_depth: max_depth.unwrap_or(defaults.max_depth),
+        arrays: match arrays.as_deref() {
+            Some(mode) => ArrayMode::parse(mode).map_err(|e| e.to_string())?,
+            None => defaults.arrays,
+        },
+        max_array_items: max_array_items.unwrap_or(defaults.max_array_items),
+    };
+    
+    let result = manager.load_flat_page(page, page_size, transform.as_deref(), &options).await;
+    
+    match &result {
+        Ok(response) => {
+            println!("成功加载展开数据: 总行数={}, 当前页数据条数={}, 列数={}", 
+                     response.total, response.data.len(), response.columns.as_ref().map_or(0, Vec::len));
+        }
+        Err(e) => {
+            println!("加载展开数据失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
 /// 搜索并加载匹配的数据
+///
+/// 找到足够的匹配后立即返回当前页，后台继续扫描并发送 `jsonl-search-progress` 事件。
 #[tauri::command]
 pub async fn search_page(
+    app_handle: AppHandle,
     keyword: &str,
     page: usize,
     page_size: usize,
+    transform: Option<String>,
     manager: State<'_, JsonlReaderManager>
 ) -> Result<PagedResponse, String> {
     println!("搜索数据: 关键词='{}', 页码={}, 每页行数={}", keyword, page, page_size);
     
-    let result = manager.search(keyword, page, page_size).await;
+    let result = manager.search(keyword, page, page_size, transform.as_deref(), move |progress| {
+        let _ = app_handle.emit("jsonl-search-progress", &progress);
+    }).await;
     
     match &result {
         Ok(response) => {
-            println!("搜索成功: 匹配行数={}, 当前页数据条数={}", 
-                     response.total, response.data.len());
+            println!("搜索成功: 匹配行数={}, 当前页数据条数={}, 扫描完成={:?}", 
+                     response.total, response.data.len(), response.complete);
         }
         Err(e) => {
             println!("搜索失败: 错误={}", e);
@@ -92,6 +141,42 @@ pub async fn search_page(
     result.map_err(|e| e.to_string())
 }
 
+/// 模糊搜索，容忍拼写错误，结果按匹配质量排序
+#[tauri::command]
+pub async fn fuzzy_search_page(
+    keyword: &str,
+    max_distance: Option<usize>,
+    page: usize,
+    page_size: usize,
+    transform: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<PagedResponse, String> {
+    println!("模糊搜索: 关键词='{}', 最大编辑距离={:?}, 页码={}, 每页行数={}", keyword, max_distance, page, page_size);
+    
+    let result = manager.fuzzy_search(keyword, max_distance, page, page_size, transform.as_deref()).await;
+    
+    match &result {
+        Ok(response) => {
+            println!("模糊搜索成功: 匹配行数={}, 当前页数据条数={}", 
+                     response.total, response.data.len());
+        }
+        Err(e) => {
+            println!("模糊搜索失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 取消正在进行的搜索
+#[tauri::command]
+pub async fn cancel_search(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<bool, String> {
+    println!("取消搜索");
+    Ok(manager.cancel_search())
+}
+
 /// 获取文件总行数
 #[tauri::command]
 pub async fn get_total_lines(
@@ -111,4 +196,830 @@ pub async fn get_total_lines(
     }
     
     result.map_err(|e| e.to_string())
-} 
\ No newline at end of file
+}
+
+/// 导出当前文件，可按关键词过滤并应用变换程序
+#[tauri::command]
+pub async fn export_jsonl(
+    output_path: &str,
+    keyword: Option<String>,
+    transform: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<ExportSummary, String> {
+    println!("导出数据: 输出={}, 关键词={:?}, 变换={:?}", output_path, keyword, transform);
+    
+    let result = manager.export(output_path, keyword.as_deref(), transform.as_deref()).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("导出成功: 写入行数={}, 跳过行数={}", summary.written, summary.skipped);
+        }
+        Err(e) => {
+            println!("导出失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 在后台为当前文件构建搜索索引
+///
+/// 构建进度通过 `jsonl-index-progress` 事件发送。
+#[tauri::command]
+pub async fn build_search_index(
+    app_handle: AppHandle,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<bool, String> {
+    println!("开始构建搜索索引");
+    
+    manager.build_search_index(move |progress| {
+        let _ = app_handle.emit("jsonl-index-progress", &progress);
+    })
+    .await
+    .map(|_| true)
+    .map_err(|e| {
+        println!("构建搜索索引失败: 错误={}", e);
+        e.to_string()
+    })
+}
+
+/// 获取当前文件的搜索索引状态
+#[tauri::command]
+pub async fn get_index_status(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<IndexStatus, String> {
+    manager.index_status()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除当前文件的搜索索引
+#[tauri::command]
+pub async fn drop_search_index(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<bool, String> {
+    println!("删除搜索索引");
+    Ok(manager.drop_search_index())
+}
+
+/// 按字段排序当前文件
+///
+/// 排序进度通过 `jsonl-sort-progress` 事件发送，完成后 `load_page` 按排序结果分页。
+#[tauri::command]
+pub async fn sort_by_field(
+    app_handle: AppHandle,
+    key_path: &str,
+    descending: bool,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<SortStatus, String> {
+    println!("排序: 字段={}, 降序={}", key_path, descending);
+    
+    manager.sort_by_field(key_path, descending, move |progress| {
+        let _ = app_handle.emit("jsonl-sort-progress", &progress);
+    })
+    .await
+    .map_err(|e| {
+        println!("排序失败: 错误={}", e);
+        e.to_string()
+    })
+}
+
+/// 取消排序，恢复原始顺序
+#[tauri::command]
+pub async fn clear_sort(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<bool, String> {
+    println!("取消排序");
+    manager.clear_sort()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取当前排序状态
+#[tauri::command]
+pub async fn get_sort_status(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<Option<SortStatus>, String> {
+    manager.sort_status()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 检测重复行
+///
+/// `mode` 为 `exact`（字节相同）、`canonical`（规范化 JSON 后相同）或 `keys`（指定字段相同）。
+#[tauri::command]
+pub async fn analyze_duplicates(
+    mode: &str,
+    key_paths: Option<Vec<String>>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<DedupSummary, String> {
+    println!("重复检测: 方式={}, 字段={:?}", mode, key_paths);
+    
+    let result = manager.analyze_duplicates(mode, &key_paths.unwrap_or_default()).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("重复检测成功: 重复组={}, 冗余行={}", summary.duplicate_groups, summary.redundant_rows);
+        }
+        Err(e) => {
+            println!("重复检测失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 分页获取重复组
+#[tauri::command]
+pub async fn get_duplicate_groups(
+    page: usize,
+    page_size: usize,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<DuplicateGroupPage, String> {
+    manager.duplicate_groups(page, page_size)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 导出去重后的文件
+///
+/// `keep` 为 `first` 或 `last`，表示每组保留的行。
+#[tauri::command]
+pub async fn export_deduplicated(
+    output_path: &str,
+    keep: &str,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<ExportSummary, String> {
+    println!("导出去重文件: 输出={}, 保留={}", output_path, keep);
+    
+    let keep_last = match keep {
+        "first" => false,
+        "last" => true,
+        other => return Err(format!("未知的保留方式: {}", other)),
+    };
+    
+    let result = manager.export_deduplicated(output_path, keep_last).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("导出去重文件成功: 写入行数={}, 删除行数={}", summary.written, summary.skipped);
+        }
+        Err(e) => {
+            println!("导出去重文件失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 检测指定文本字段的近似重复行
+///
+/// `threshold` 为估计的 Jaccard 相似度阈值 (0-1]，检测进度通过 `jsonl-near-dup-progress` 事件发送。
+#[tauri::command]
+pub async fn analyze_near_duplicates(
+    app_handle: AppHandle,
+    field: &str,
+    threshold: f32,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<NearDupSummary, String> {
+    println!("近似重复检测: 字段={}, 阈值={}", field, threshold);
+    
+    let result = manager.analyze_near_duplicates(field, threshold, move |progress| {
+        let _ = app_handle.emit("jsonl-near-dup-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("近似重复检测成功: 簇数={}, 涉及行数={}", summary.clusters, summary.clustered_rows);
+        }
+        Err(e) => {
+            println!("近似重复检测失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 分页获取近似重复簇
+#[tauri::command]
+pub async fn get_near_duplicate_clusters(
+    page: usize,
+    page_size: usize,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<DuplicateGroupPage, String> {
+    manager.near_duplicate_clusters(page, page_size)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 推断当前文件的字段结构
+///
+/// `sample_size` 为空时扫描全部行，否则均匀抽样；结果按文件缓存，`refresh` 为真时强制重新计算。
+/// 推断进度通过 `jsonl-schema-progress` 事件发送。
+#[tauri::command]
+pub async fn infer_schema(
+    app_handle: AppHandle,
+    sample_size: Option<usize>,
+    refresh: Option<bool>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<SchemaReport, String> {
+    println!("推断字段结构: 抽样行数={:?}", sample_size);
+    
+    let result = manager.infer_schema(sample_size, refresh.unwrap_or(false), move |progress| {
+        let _ = app_handle.emit("jsonl-schema-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(report) => {
+            println!("推断字段结构成功: 行数={}, 字段数={}", report.rows_scanned, report.fields.len());
+        }
+        Err(e) => {
+            println!("推断字段结构失败: 错误={}", e);
+        }
+    }
+    
+    result.map(|report| (*report).clone()).map_err(|e| e.to_string())
+}
+
+/// 将推断出的字段结构导出为 JSON Schema 文件
+///
+/// `required_threshold` 为列为必需字段的最低出现比例（默认 1，即总是出现），
+/// `enum_max_values` 为识别为枚举的字符串字段最多的不同值数量（默认 10，0 表示不识别）。
+#[tauri::command]
+pub async fn export_inferred_schema(
+    app_handle: AppHandle,
+    output_path: &str,
+    sample_size: Option<usize>,
+    required_threshold: Option<f32>,
+    enum_max_values: Option<usize>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<JsonValue, String> {
+    println!("导出 JSON Schema: 输出={}, 必需比例={:?}, 枚举上限={:?}", output_path, required_threshold, enum_max_values);
+    
+    let defaults = SchemaExportOptions::default();
+    let options = SchemaExportOptions {
+        required_threshold: required_threshold.unwrap_or(defaults.required_threshold),
+        enum_max_values: enum_max_values.unwrap_or(defaults.enum_max_values),
+    };
+    
+    let result = manager.export_inferred_schema(output_path, sample_size, &options, move |progress| {
+        let _ = app_handle.emit("jsonl-schema-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(_) => {
+            println!("导出 JSON Schema 成功: {}", output_path);
+        }
+        Err(e) => {
+            println!("导出 JSON Schema 失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 计算字段统计：数值摘要、近似分位数、高频值、字符串长度分布与不同值数量估计
+///
+/// `keyword` 非空时只统计匹配的行，统计进度通过 `jsonl-stats-progress` 事件发送。
+#[tauri::command]
+pub async fn get_field_statistics(
+    app_handle: AppHandle,
+    fields: Vec<String>,
+    keyword: Option<String>,
+    top_k: Option<usize>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<FieldStatsReport, String> {
+    println!("计算字段统计: 字段={:?}, 关键词={:?}", fields, keyword);
+    
+    let result = manager.field_statistics(&fields, keyword.as_deref(), top_k.unwrap_or(10), move |progress| {
+        let _ = app_handle.emit("jsonl-stats-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(report) => {
+            println!("计算字段统计成功: 行数={}", report.rows);
+        }
+        Err(e) => {
+            println!("计算字段统计失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 图表：数值字段的直方图，默认 20 个区间
+#[tauri::command]
+pub async fn get_chart_histogram(
+    field: &str,
+    bins: Option<usize>,
+    keyword: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<HistogramData, String> {
+    println!("图表直方图: 字段={}, 区间数={:?}, 关键词={:?}", field, bins, keyword);
+    
+    manager.chart_histogram(field, bins.unwrap_or(20), keyword.as_deref())
+        .await
+        .map_err(|e| {
+            println!("图表直方图失败: 错误={}", e);
+            e.to_string()
+        })
+}
+
+/// 图表：类别字段的计数，默认取前 20 个类别
+#[tauri::command]
+pub async fn get_chart_categories(
+    field: &str,
+    limit: Option<usize>,
+    keyword: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<CategoryData, String> {
+    println!("图表类别计数: 字段={}, 数量={:?}, 关键词={:?}", field, limit, keyword);
+    
+    manager.chart_categories(field, limit.unwrap_or(20), keyword.as_deref())
+        .await
+        .map_err(|e| {
+            println!("图表类别计数失败: 错误={}", e);
+            e.to_string()
+        })
+}
+
+/// 图表：日期字段的时间序列，`interval` 为 `day`、`week` 或 `month`
+#[tauri::command]
+pub async fn get_chart_time_series(
+    field: &str,
+    interval: &str,
+    keyword: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<TimeSeriesData, String> {
+    println!("图表时间序列: 字段={}, 粒度={}, 关键词={:?}", field, interval, keyword);
+    
+    manager.chart_time_series(field, interval, keyword.as_deref())
+        .await
+        .map_err(|e| {
+            println!("图表时间序列失败: 错误={}", e);
+            e.to_string()
+        })
+}
+
+/// 图表：两个数值字段的散点抽样，默认最多 1000 个点
+#[tauri::command]
+pub async fn get_chart_scatter(
+    x_field: &str,
+    y_field: &str,
+    limit: Option<usize>,
+    keyword: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<ScatterData, String> {
+    println!("图表散点: 横轴={}, 纵轴={}, 数量={:?}, 关键词={:?}", x_field, y_field, limit, keyword);
+    
+    manager.chart_scatter(x_field, y_field, limit.unwrap_or(1000), keyword.as_deref())
+        .await
+        .map_err(|e| {
+            println!("图表散点失败: 错误={}", e);
+            e.to_string()
+        })
+}
+
+/// 使用 JSON Schema 文件校验当前文件的所有行
+///
+/// `draft` 为 `draft7` 或 `2020-12`，为空时根据 `$schema` 自动识别。
+/// 校验进度通过 `jsonl-validation-progress` 事件发送。
+#[tauri::command]
+pub async fn validate_with_schema(
+    app_handle: AppHandle,
+    schema_path: &str,
+    draft: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<ValidationSummary, String> {
+    println!("Schema 校验: Schema={}, 版本={:?}", schema_path, draft);
+    
+    let result = manager.validate_with_schema(schema_path, draft.as_deref(), move |progress| {
+        let _ = app_handle.emit("jsonl-validation-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("Schema 校验成功: 不合法行数={}, 违规数={}", summary.invalid_rows, summary.violations);
+        }
+        Err(e) => {
+            println!("Schema 校验失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 分页获取 Schema 违规列表
+#[tauri::command]
+pub async fn get_schema_violations(
+    page: usize,
+    page_size: usize,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<ViolationPage, String> {
+    manager.schema_violations(page, page_size)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 分页加载不符合 Schema 的行
+#[tauri::command]
+pub async fn load_invalid_page(
+    page: usize,
+    page_size: usize,
+    transform: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<PagedResponse, String> {
+    println!("加载不合法行: 页码={}, 每页行数={}", page, page_size);
+    
+    manager.load_invalid_page(page, page_size, transform.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 检查当前文件的数据质量
+///
+/// 报告缺失常见字段、空字符串、类型不一致、过长或过短的值、高频重复内容及无法解析的行，
+/// 检查进度通过 `jsonl-quality-progress` 事件发送。
+#[tauri::command]
+pub async fn get_quality_report(
+    app_handle: AppHandle,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<QualityReport, String> {
+    println!("数据质量检查");
+    
+    let result = manager.quality_report(move |progress| {
+        let _ = app_handle.emit("jsonl-quality-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(report) => {
+            println!("数据质量检查成功: 问题数={}", report.issues.len());
+        }
+        Err(e) => {
+            println!("数据质量检查失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 分页加载某个质量问题涉及的行，`issue` 为问题在报告中的下标
+#[tauri::command]
+pub async fn load_quality_issue_page(
+    issue: usize,
+    page: usize,
+    page_size: usize,
+    transform: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<PagedResponse, String> {
+    println!("加载质量问题行: 问题={}, 页码={}, 每页行数={}", issue, page, page_size);
+    
+    manager.load_quality_issue_page(issue, page, page_size, transform.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 比较两个 JSONL 文件
+///
+/// `key_field` 为空时按行号逐行对齐，否则按该字段（如 `id`）的值对齐，
+/// 比较进度通过 `jsonl-diff-progress` 事件发送。
+#[tauri::command]
+pub async fn diff_files(
+    app_handle: AppHandle,
+    left_path: &str,
+    right_path: &str,
+    key_field: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<DiffSummary, String> {
+    println!("比较文件: 左={}, 右={}, 对齐字段={:?}", left_path, right_path, key_field);
+    
+    let result = manager.diff_files(left_path, right_path, key_field.as_deref(), move |progress| {
+        let _ = app_handle.emit("jsonl-diff-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("比较文件成功: 新增={}, 删除={}, 修改={}", summary.added, summary.removed, summary.changed);
+        }
+        Err(e) => {
+            println!("比较文件失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 分页获取文件比较的差异，`kind` 为 added / removed / changed 时只返回该类型
+#[tauri::command]
+pub async fn get_diff_page(
+    page: usize,
+    page_size: usize,
+    kind: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<DiffPage, String> {
+    println!("获取比较差异: 页码={}, 每页数量={}, 类型={:?}", page, page_size, kind);
+    
+    manager.diff_page(page, page_size, kind.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 比较任意两行，`path` 为空的行引用指当前打开的文件
+///
+/// 返回新增、删除和修改的字段路径，较长的字符串附带字符级差异。
+#[tauri::command]
+pub async fn diff_rows(
+    left: RowRef,
+    right: RowRef,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<RowComparison, String> {
+    println!("比较行: 左={:?}, 右={:?}", left, right);
+    
+    manager.diff_rows(&left, &right)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置某一行中指定字段的值，返回编辑后的整行
+///
+/// `path` 为字段路径，如 `address.city`、`tags[0]`；编辑在保存前只记录在编辑日志中，
+/// 分页和搜索返回编辑后的内容。
+#[tauri::command]
+pub async fn set_field_value(
+    line: usize,
+    path: &str,
+    value: JsonValue,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<JsonObject, String> {
+    println!("编辑字段: 行号={}, 路径={}", line, path);
+    
+    let result = manager.set_field_value(line, path, value).await;
+    
+    if let Err(e) = &result {
+        println!("编辑字段失败: 错误={}", e);
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 获取尚未保存的编辑状态
+#[tauri::command]
+pub async fn get_edit_status(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    manager.edit_status()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 丢弃全部尚未保存的编辑
+#[tauri::command]
+pub async fn discard_edits(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("丢弃全部编辑");
+    
+    manager.discard_edits()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 为所有行添加字段
+///
+/// `expression` 为 jq 表达式时按每一行计算字段值（如 `.prompt | length`），否则使用 `value`；
+/// `overwrite` 为真时覆盖已有的值。`keyword` 与 `condition`（jq 条件表达式）用于只修改满足条件的行。
+#[tauri::command]
+pub async fn add_field(
+    path: &str,
+    value: Option<JsonValue>,
+    expression: Option<String>,
+    overwrite: Option<bool>,
+    keyword: Option<String>,
+    condition: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("添加字段: 路径={}, 表达式={:?}, 关键词={:?}, 条件={:?}", path, expression, keyword, condition);
+    
+    manager.add_field(path, value, expression.as_deref(), overwrite.unwrap_or(false), keyword.as_deref(), condition.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除所有行（或满足条件的行）中的字段
+#[tauri::command]
+pub async fn remove_field(
+    path: &str,
+    keyword: Option<String>,
+    condition: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("删除字段: 路径={}, 关键词={:?}, 条件={:?}", path, keyword, condition);
+    
+    manager.remove_field(path, keyword.as_deref(), condition.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 重命名或移动所有行（或满足条件的行）中的字段，目标字段已存在时被覆盖
+#[tauri::command]
+pub async fn rename_field(
+    from: &str,
+    to: &str,
+    keyword: Option<String>,
+    condition: Option<String>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("重命名字段: {} -> {}, 关键词={:?}, 条件={:?}", from, to, keyword, condition);
+    
+    manager.rename_field(from, to, keyword.as_deref(), condition.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 在指定行号处插入若干行，`position` 等于总行数时追加到末尾
+#[tauri::command]
+pub async fn insert_rows(
+    position: usize,
+    rows: Vec<JsonValue>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("插入行: 位置={}, 行数={}", position, rows.len());
+    
+    manager.insert_rows(position, rows)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除指定行号上的行
+#[tauri::command]
+pub async fn delete_rows(
+    lines: Vec<usize>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("删除行: 行数={}", lines.len());
+    
+    manager.delete_rows(&lines)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 复制指定行号上的行，副本插入在原行之后
+#[tauri::command]
+pub async fn duplicate_rows(
+    lines: Vec<usize>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("复制行: 行数={}", lines.len());
+    
+    manager.duplicate_rows(&lines)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取编辑历史，包括已撤销、可以重做的操作
+#[tauri::command]
+pub async fn get_edit_history(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditHistory, String> {
+    manager.edit_history()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 撤销最近的一次编辑
+#[tauri::command]
+pub async fn undo_edit(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    manager.undo_edit()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 重做最近撤销的一次编辑
+#[tauri::command]
+pub async fn redo_edit(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    manager.redo_edit()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 把尚未保存的编辑写入原文件
+///
+/// 先写入临时文件再原子地替换原文件，`backup` 为真时保留 `.bak` 备份；
+/// 写入与重建行索引的进度通过 `jsonl-save-progress` 事件发送。
+/// 文件在编辑期间被其他程序修改时返回以“保存冲突”开头的错误，
+/// 可改用 `save_edits_as` 另存为新文件，或用 `rebase_edits` 移植编辑后再保存。
+#[tauri::command]
+pub async fn save_edits(
+    app_handle: AppHandle,
+    backup: Option<bool>,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<SaveSummary, String> {
+    println!("保存编辑: 备份={:?}", backup);
+    
+    let result = manager.save_edits(backup.unwrap_or(false), move |progress| {
+        let _ = app_handle.emit("jsonl-save-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("保存编辑成功: 文件={}, 行数={}", summary.path, summary.rows);
+            if let Some(reopen_error) = &summary.reopen_error {
+                println!("重新打开文件失败: 错误={}", reopen_error);
+            }
+        }
+        Err(e) => {
+            println!("保存编辑失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 获取当前文件的编辑草稿状态，没有草稿时返回空
+///
+/// 编辑会自动保存为草稿，打开文件后可据此提示用户恢复上次未保存的编辑。
+#[tauri::command]
+pub async fn get_draft_status(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<Option<DraftStatus>, String> {
+    manager.draft_status()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 恢复当前文件的编辑草稿，文件在草稿保存后被修改时拒绝恢复
+#[tauri::command]
+pub async fn restore_draft(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<EditStatus, String> {
+    println!("恢复编辑草稿");
+    
+    manager.restore_draft()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除当前文件的编辑草稿
+#[tauri::command]
+pub async fn discard_draft(
+    manager: State<'_, JsonlReaderManager>
+) -> Result<bool, String> {
+    println!("删除编辑草稿");
+    
+    manager.discard_draft()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 把编辑后的内容另存为新文件并打开新文件，原文件保持不变
+///
+/// 写入与重建行索引的进度通过 `jsonl-save-progress` 事件发送。
+#[tauri::command]
+pub async fn save_edits_as(
+    app_handle: AppHandle,
+    target: &str,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<SaveSummary, String> {
+    println!("另存编辑: 目标={}", target);
+    
+    let result = manager.save_edits_as(target, move |progress| {
+        let _ = app_handle.emit("jsonl-save-progress", &progress);
+    }).await;
+    
+    match &result {
+        Ok(summary) => {
+            println!("另存编辑成功: 文件={}, 行数={}", summary.path, summary.rows);
+            if let Some(reopen_error) = &summary.reopen_error {
+                println!("重新打开文件失败: 错误={}", reopen_error);
+            }
+        }
+        Err(e) => {
+            println!("另存编辑失败: 错误={}", e);
+        }
+    }
+    
+    result.map_err(|e| e.to_string())
+}
+
+/// 文件被其他程序修改后，把编辑移植到文件的新内容上
+///
+/// 按行号的字段修改移到内容相同的行上，找不到时丢弃；批量操作重新求值，增删行被丢弃；
+/// 重建行索引的进度通过 `jsonl-loading-progress` 事件发送。
+#[tauri::command]
+pub async fn rebase_edits(
+    app_handle: AppHandle,
+    manager: State<'_, JsonlReaderManager>
+) -> Result<RebaseSummary, String> {
+    println!("移植编辑到文件的新内容");
+    
+    manager.rebase_edits(move |progress| {
+        let _ = app_handle.emit("jsonl-loading-progress", &progress);
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,403 @@
+use crate::jsonl::{canonical_json, FrequentValues, JsonPath, MappedLines};
+use crate::models::{
+    AppError, AppResult, CategoryData, HistogramBucket, HistogramData, JsonValue, ScatterData, ScatterPoint, TimeSeriesData,
+    TimeSeriesPoint, ValueCount,
+};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// 直方图最多的区间数
+pub const MAX_HISTOGRAM_BINS: usize = 200;
+
+/// 柱状图最多的类别数
+pub const MAX_CATEGORIES: usize = 100;
+
+/// 时间序列最多的点数
+pub const MAX_TIME_POINTS: usize = 1000;
+
+/// 散点图最多的点数
+pub const MAX_SCATTER_POINTS: usize = 10_000;
+
+/// 每天的毫秒数
+const DAY_MS: i64 = 86_400_000;
+
+/// 读取一行中指定字段的数值
+fn numeric_value(line: &str, path: &JsonPath) -> Option<f64> {
+    let row = serde_json::from_str::<JsonValue>(line).ok()?;
+    path.get(&row)?.as_f64().filter(|n| n.is_finite())
+}
+
+/// 数值字段的等宽直方图
+pub fn histogram(path: &str, line_offsets: &[u64], lines: &[usize], field: &str, bins: usize) -> AppResult<HistogramData> {
+    let json_path = JsonPath::parse(field)?;
+    let bins = bins.clamp(1, MAX_HISTOGRAM_BINS);
+    let mapped = MappedLines::open(path, line_offsets)?;
+
+    let values = mapped.par_filter_map_lines(lines, |_, line| numeric_value(line, &json_path));
+    let missing = lines.len() - values.len();
+
+    let (min, max) = values
+        .par_iter()
+        .fold(|| (f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)))
+        .reduce(|| (f64::INFINITY, f64::NEG_INFINITY), |a, b| (a.0.min(b.0), a.1.max(b.1)));
+
+    let mut buckets = Vec::new();
+    if !values.is_empty() {
+        // 所有值相同时使用宽度为 1 的单个区间
+        let width = if max > min { (max - min) / bins as f64 } else { 1.0 };
+        let bins = if max > min { bins } else { 1 };
+        let counts = values
+            .par_iter()
+            .fold(
+                || vec![0usize; bins],
+                |mut counts, &v| {
+                    let bin = (((v - min) / width) as usize).min(bins - 1);
+                    counts[bin] += 1;
+                    counts
+                },
+            )
+            .reduce(
+                || vec![0usize; bins],
+                |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                    a
+                },
+            );
+        buckets = counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| HistogramBucket {
+                start: min + width * i as f64,
+                end: if i + 1 == bins && max > min { max } else { min + width * (i + 1) as f64 },
+                count,
+            })
+            .collect();
+    }
+
+    Ok(HistogramData {
+        field: field.to_string(),
+        count: values.len(),
+        missing,
+        buckets,
+    })
+}
+
+/// 类别字段的计数，按次数降序取前 `limit` 个，其余计入 `other`
+pub fn category_counts(path: &str, line_offsets: &[u64], lines: &[usize], field: &str, limit: usize) -> AppResult<CategoryData> {
+    let json_path = JsonPath::parse(field)?;
+    let limit = limit.clamp(1, MAX_CATEGORIES);
+    let capacity = (limit * 10).max(1000);
+    let mapped = MappedLines::open(path, line_offsets)?;
+
+    let (present, frequent) = lines
+        .par_iter()
+        .fold(
+            || (0usize, FrequentValues::new(capacity), String::new()),
+            |(mut present, mut frequent, mut key), &line_num| {
+                let value = mapped
+                    .line(line_num)
+                    .filter(|line| !line.is_empty())
+                    .and_then(|line| serde_json::from_str::<JsonValue>(line).ok())
+                    .and_then(|row| json_path.get(&row).cloned());
+                if let Some(value) = value {
+                    present += 1;
+                    key.clear();
+                    canonical_json(&value, &mut key);
+                    frequent.insert(&key);
+                }
+                (present, frequent, key)
+            },
+        )
+        .map(|(present, frequent, _)| (present, frequent))
+        .reduce(
+            || (0, FrequentValues::new(capacity)),
+            |(a_present, mut a), (b_present, b)| {
+                a.merge(b);
+                (a_present + b_present, a)
+            },
+        );
+
+    let categories: Vec<ValueCount> = frequent
+        .top(limit)
+        .into_iter()
+        .filter_map(|(key, count)| {
+            serde_json::from_str::<JsonValue>(key).ok().map(|value| ValueCount {
+                value,
+                count: count as usize,
+            })
+        })
+        .collect();
+    let shown: usize = categories.iter().map(|c| c.count).sum();
+
+    Ok(CategoryData {
+        field: field.to_string(),
+        categories,
+        other: present - shown,
+        missing: lines.len() - present,
+        exact: frequent.is_exact(),
+    })
+}
+
+/// 时间序列的分桶粒度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeInterval {
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeInterval {
+    /// 从前端参数解析
+    pub fn parse(interval: &str) -> AppResult<Self> {
+        match interval {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            other => Err(AppError::General(format!("未知的时间粒度: {}", other))),
+        }
+    }
+
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// 时间戳所在桶的起始日（自 1970-01-01 起的天数）
+    fn bucket_start(&self, timestamp_ms: i64) -> i64 {
+        let days = timestamp_ms.div_euclid(DAY_MS);
+        match self {
+            Self::Day => days,
+            // 1970-01-01 是星期四，周一为一周的开始
+            Self::Week => days - (days + 3).rem_euclid(7),
+            Self::Month => {
+                let (year, month, _) = civil_from_days(days);
+                days_from_civil(year, month, 1)
+            }
+        }
+    }
+
+    /// 桶的标签
+    fn label(&self, start_day: i64) -> String {
+        let (year, month, day) = civil_from_days(start_day);
+        match self {
+            Self::Day | Self::Week => format!("{:04}-{:02}-{:02}", year, month, day),
+            Self::Month => format!("{:04}-{:02}", year, month),
+        }
+    }
+}
+
+/// 公历日期转换为自 1970-01-01 起的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// 自 1970-01-01 起的天数转换为公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// 解析固定位数的数字
+fn digits(text: &str, range: std::ops::Range<usize>) -> Option<u32> {
+    let part = text.get(range)?;
+    if part.bytes().all(|b| b.is_ascii_digit()) {
+        part.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// 将日期字段解析为 Unix 毫秒时间戳
+///
+/// 支持 `YYYY-MM-DD`、`YYYY/MM/DD` 及带时间和时区的 ISO 8601 字符串，
+/// 以及秒或毫秒级的 Unix 时间戳（数字或数字字符串）。
+pub fn parse_timestamp_ms(value: &JsonValue) -> Option<i64> {
+    let from_number = |n: f64| {
+        if !n.is_finite() {
+            return None;
+        }
+        // 绝对值超过 1e11 视为毫秒
+        Some(if n.abs() >= 1e11 { n as i64 } else { (n * 1000.0) as i64 })
+    };
+
+    let text = match value {
+        JsonValue::Number(n) => return from_number(n.as_f64()?),
+        JsonValue::String(text) => text.trim(),
+        _ => return None,
+    };
+    if let Ok(n) = text.parse::<f64>() {
+        return from_number(n);
+    }
+
+    let year = digits(text, 0..4)? as i64;
+    let sep = text.as_bytes().get(4).copied()?;
+    if (sep != b'-' && sep != b'/') || text.as_bytes().get(7) != Some(&sep) {
+        return None;
+    }
+    let month = digits(text, 5..7)?;
+    let day = digits(text, 8..10)?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut ms = days_from_civil(year, month, day) * DAY_MS;
+
+    let rest = &text[10..];
+    let Some(time) = rest.strip_prefix(['T', 't', ' ']) else {
+        return rest.is_empty().then_some(ms);
+    };
+    let hour = digits(time, 0..2)?;
+    let minute = digits(time, 3..5)?;
+    if time.as_bytes().get(2) != Some(&b':') || hour > 23 || minute > 59 {
+        return None;
+    }
+    let mut rest = &time[5..];
+    let mut second = 0;
+    if let Some(after) = rest.strip_prefix(':') {
+        second = digits(after, 0..2)?;
+        rest = &after[2..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let end = fraction.find(|c: char| !c.is_ascii_digit()).unwrap_or(fraction.len());
+            let millis = format!("{:0<3}", &fraction[..end.min(3)]);
+            ms += millis.parse::<i64>().ok()?;
+            rest = &fraction[end..];
+        }
+    }
+    ms += (hour as i64 * 3600 + minute as i64 * 60 + second as i64) * 1000;
+
+    // 时区偏移，缺省按 UTC 处理
+    match rest {
+        "" | "Z" | "z" => Some(ms),
+        _ => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let offset = rest[1..].replace(':', "");
+            let offset_hours = digits(&offset, 0..2)? as i64;
+            let offset_minutes = if offset.len() > 2 { digits(&offset, 2..4)? as i64 } else { 0 };
+            Some(ms - sign * (offset_hours * 60 + offset_minutes) * 60_000)
+        }
+    }
+}
+
+/// 按天、周或月统计日期字段的行数
+pub fn time_series(path: &str, line_offsets: &[u64], lines: &[usize], field: &str, interval: TimeInterval) -> AppResult<TimeSeriesData> {
+    let json_path = JsonPath::parse(field)?;
+    let mapped = MappedLines::open(path, line_offsets)?;
+
+    let (present, counts) = lines
+        .par_iter()
+        .fold(
+            || (0usize, BTreeMap::<i64, usize>::new()),
+            |(mut present, mut counts), &line_num| {
+                let value = mapped
+                    .line(line_num)
+                    .filter(|line| !line.is_empty())
+                    .and_then(|line| serde_json::from_str::<JsonValue>(line).ok())
+                    .and_then(|row| json_path.get(&row).cloned());
+                if let Some(value) = value {
+                    present += 1;
+                    if let Some(ms) = parse_timestamp_ms(&value) {
+                        *counts.entry(interval.bucket_start(ms)).or_insert(0) += 1;
+                    }
+                }
+                (present, counts)
+            },
+        )
+        .reduce(
+            || (0, BTreeMap::new()),
+            |(a_present, mut a), (b_present, b)| {
+                for (bucket, count) in b {
+                    *a.entry(bucket).or_insert(0) += count;
+                }
+                (a_present + b_present, a)
+            },
+        );
+
+    let parsed: usize = counts.values().sum();
+    let truncated = counts.len() > MAX_TIME_POINTS;
+    let points = counts
+        .into_iter()
+        .take(MAX_TIME_POINTS)
+        .map(|(start_day, count)| TimeSeriesPoint {
+            bucket: interval.label(start_day),
+            start: start_day * DAY_MS,
+            count,
+        })
+        .collect();
+
+    Ok(TimeSeriesData {
+        field: field.to_string(),
+        interval: interval.name().to_string(),
+        points,
+        unparsed: present - parsed,
+        missing: lines.len() - present,
+        truncated,
+    })
+}
+
+/// 两个数值字段的散点抽样
+///
+/// 两个字段都是数值的行超过 `limit` 时按固定间隔抽样，结果按行号排序。
+pub fn scatter_sample(
+    path: &str,
+    line_offsets: &[u64],
+    lines: &[usize],
+    x_field: &str,
+    y_field: &str,
+    limit: usize,
+) -> AppResult<ScatterData> {
+    let x_path = JsonPath::parse(x_field)?;
+    let y_path = JsonPath::parse(y_field)?;
+    let limit = limit.clamp(1, MAX_SCATTER_POINTS);
+    let mapped = MappedLines::open(path, line_offsets)?;
+
+    // 只保留行号，抽样后再读取数值，避免在内存中保存所有点
+    let eligible = mapped.par_filter_map_lines(lines, |line_num, line| {
+        let row = serde_json::from_str::<JsonValue>(line).ok()?;
+        x_path.get(&row)?.as_f64().filter(|n| n.is_finite())?;
+        y_path.get(&row)?.as_f64().filter(|n| n.is_finite())?;
+        Some(line_num)
+    });
+    let total = eligible.len();
+
+    let sampled: Vec<usize> = if total > limit {
+        (0..limit).map(|i| eligible[i * total / limit]).collect()
+    } else {
+        eligible
+    };
+    let points = mapped.par_filter_map_lines(&sampled, |line_num, line| {
+        let row = serde_json::from_str::<JsonValue>(line).ok()?;
+        Some(ScatterPoint {
+            line: line_num,
+            x: x_path.get(&row)?.as_f64()?,
+            y: y_path.get(&row)?.as_f64()?,
+        })
+    });
+
+    Ok(ScatterData {
+        x_field: x_field.to_string(),
+        y_field: y_field.to_string(),
+        total,
+        points,
+    })
+}
@@ -105,6 +105,7 @@ impl DebugDataGenerator {
         Ok(PagedResponse {
             data,
             total: self.total_rows,
+            ..Default::default()
         })
     }
     
@@ -143,6 +144,7 @@ impl DebugDataGenerator {
         Ok(PagedResponse {
             data,
             total: matched_total,
+            ..Default::default()
         })
     }
 }
@@ -0,0 +1,193 @@
+use crate::jsonl::{JsonPath, MappedLines};
+use crate::models::{AppError, AppResult, JsonValue};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// 重复判定方式
+#[derive(Debug, Clone, PartialEq)]
+pub enum DedupMode {
+    /// 字节完全相同（忽略行首尾空白）
+    Exact,
+
+    /// 规范化 JSON 后相同（忽略字段顺序与空白）
+    Canonical,
+
+    /// 指定字段的值相同
+    Keys(Vec<JsonPath>),
+}
+
+impl DedupMode {
+    /// 从前端参数解析
+    pub fn parse(mode: &str, key_paths: &[String]) -> AppResult<Self> {
+        match mode {
+            "exact" => Ok(Self::Exact),
+            "canonical" => Ok(Self::Canonical),
+            "keys" => {
+                if key_paths.is_empty() {
+                    return Err(AppError::General("按字段去重需要至少一个字段路径".to_string()));
+                }
+                let paths = key_paths
+                    .iter()
+                    .map(|path| JsonPath::parse(path))
+                    .collect::<AppResult<Vec<_>>>()?;
+                Ok(Self::Keys(paths))
+            }
+            other => Err(AppError::General(format!("未知的去重方式: {}", other))),
+        }
+    }
+
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Canonical => "canonical",
+            Self::Keys(_) => "keys",
+        }
+    }
+
+    /// 计算一行的去重键，不参与去重的行返回 `None`
+    fn key(&self, line: &str) -> Option<String> {
+        match self {
+            Self::Exact => Some(line.to_string()),
+            Self::Canonical => {
+                let value = serde_json::from_str::<JsonValue>(line).ok()?;
+                let mut key = String::new();
+                canonical_json(&value, &mut key);
+                Some(key)
+            }
+            Self::Keys(paths) => {
+                let value = serde_json::from_str::<JsonValue>(line).ok()?;
+                let mut key = String::new();
+                let mut any_present = false;
+                for path in paths {
+                    match path.get(&value) {
+                        Some(field) => {
+                            any_present = true;
+                            canonical_json(field, &mut key);
+                        }
+                        // 用 JSON 中不会出现的字符表示缺失
+                        None => key.push('\u{0}'),
+                    }
+                    key.push('\u{1f}');
+                }
+                // 所有字段都缺失的行不参与去重
+                any_present.then_some(key)
+            }
+        }
+    }
+}
+
+/// 以规范形式序列化 JSON：对象字段按名称排序，不含多余空白
+pub fn canonical_json(value: &JsonValue, out: &mut String) {
+    match value {
+        JsonValue::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{}", JsonValue::String(key.clone()));
+                out.push(':');
+                canonical_json(&map[key], out);
+            }
+            out.push('}');
+        }
+        JsonValue::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        other => {
+            let _ = write!(out, "{}", other);
+        }
+    }
+}
+
+/// 计算 128 位哈希，降低大文件中的碰撞概率
+pub(crate) fn hash128(key: &str) -> u128 {
+    let mut high = DefaultHasher::new();
+    0u8.hash(&mut high);
+    key.hash(&mut high);
+
+    let mut low = DefaultHasher::new();
+    1u8.hash(&mut low);
+    key.hash(&mut low);
+
+    ((high.finish() as u128) << 64) | low.finish() as u128
+}
+
+/// 重复分析结果
+pub struct DedupAnalysis {
+    /// 文件路径
+    pub path: String,
+
+    /// 判定方式
+    pub mode: DedupMode,
+
+    /// 参与比较的行数
+    pub compared_lines: usize,
+
+    /// 重复组，每组为升序行号，按组大小降序、首行升序排列
+    pub groups: Vec<Vec<usize>>,
+}
+
+impl DedupAnalysis {
+    /// 并行分析文件中的重复行
+    pub fn analyze(path: &str, line_offsets: &[u64], mode: DedupMode) -> AppResult<Self> {
+        let lines = MappedLines::open(path, line_offsets)?;
+
+        // 只保留哈希与行号，排序后相邻的相同哈希即为一组
+        let mut keyed: Vec<(u128, u32)> = lines.par_filter_map(|line_num, line| {
+            mode.key(line).map(|key| (hash128(&key), line_num as u32))
+        });
+        let compared_lines = keyed.len();
+        keyed.par_sort_unstable();
+
+        let mut groups = Vec::new();
+        let mut start = 0;
+        while start < keyed.len() {
+            let mut end = start + 1;
+            while end < keyed.len() && keyed[end].0 == keyed[start].0 {
+                end += 1;
+            }
+            if end - start > 1 {
+                groups.push(keyed[start..end].iter().map(|(_, line)| *line as usize).collect::<Vec<_>>());
+            }
+            start = end;
+        }
+
+        groups.sort_by(|a: &Vec<usize>, b: &Vec<usize>| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+
+        Ok(Self {
+            path: path.to_string(),
+            mode,
+            compared_lines,
+            groups,
+        })
+    }
+
+    /// 重复行总数（每组除保留的一行外的行数）
+    pub fn redundant_rows(&self) -> usize {
+        self.groups.iter().map(|group| group.len() - 1).sum()
+    }
+
+    /// 去重时需要丢弃的行号
+    pub fn rows_to_drop(&self, keep_last: bool) -> HashSet<usize> {
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                let kept = if keep_last { group[group.len() - 1] } else { group[0] };
+                group.iter().copied().filter(move |&line| line != kept)
+            })
+            .collect()
+    }
+}
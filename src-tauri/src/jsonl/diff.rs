@@ -0,0 +1,498 @@
+use crate::jsonl::{canonical_json, hash128, push_key, text_diff, FileFingerprint, JsonPath, JsonlReader, MappedLines, MAX_TEXT_DIFF_CHARS};
+use crate::models::{AppError, AppResult, DiffSummary, FieldChange, JsonValue, LoadingProgress, RowComparison, RowDiff};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+
+/// 每批并行比较的行数，每批结束后报告一次进度
+const BATCH_LINES: usize = 50_000;
+
+/// 任一侧超过该字符数的字符串在比较单行时附带字符级差异
+const LONG_STRING_CHARS: usize = 50;
+
+/// 行差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// 只在右侧出现
+    Added,
+
+    /// 只在左侧出现
+    Removed,
+
+    /// 两侧都有但内容不同
+    Changed,
+}
+
+impl DiffKind {
+    /// 从前端参数解析
+    pub fn parse(kind: &str) -> AppResult<Self> {
+        match kind {
+            "added" => Ok(Self::Added),
+            "removed" => Ok(Self::Removed),
+            "changed" => Ok(Self::Changed),
+            other => Err(AppError::General(format!("未知的差异类型: {}", other))),
+        }
+    }
+
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+/// 一处行差异，只记录行号，字段差异在分页加载时计算
+#[derive(Debug, Clone, Copy)]
+pub struct DiffEntry {
+    pub kind: DiffKind,
+    pub left_line: Option<usize>,
+    pub right_line: Option<usize>,
+}
+
+/// 比较中的一个文件
+pub struct DiffSide {
+    /// 文件路径
+    pub path: String,
+
+    /// 比较时的文件指纹
+    pub fingerprint: FileFingerprint,
+
+    /// 每行的起始字节偏移
+    pub line_offsets: Vec<u64>,
+}
+
+impl DiffSide {
+    /// 打开文件并建立行偏移
+    fn open(path: &str) -> AppResult<Self> {
+        let mut reader = JsonlReader::new(path)?;
+        reader.count_lines_with_progress(|_| {})?;
+        Ok(Self {
+            path: path.to_string(),
+            fingerprint: FileFingerprint::of(path)?,
+            line_offsets: reader.line_offsets().to_vec(),
+        })
+    }
+
+    /// 映射文件，文件在比较后发生变化时返回错误
+    fn map(&self) -> AppResult<MappedLines<'_>> {
+        if FileFingerprint::of(&self.path)? != self.fingerprint {
+            return Err(AppError::General(format!("文件已被修改，请重新比较: {}", self.path)));
+        }
+        MappedLines::open(&self.path, &self.line_offsets)
+    }
+}
+
+/// 两个 JSONL 文件的比较结果
+pub struct FileDiff {
+    /// 左侧（旧）文件
+    pub left: DiffSide,
+
+    /// 右侧（新）文件
+    pub right: DiffSide,
+
+    /// 对齐字段，为空时按行号对齐
+    pub key_field: Option<JsonPath>,
+
+    /// 摘要
+    pub summary: DiffSummary,
+
+    /// 全部差异，删除与修改按左侧行号排列，新增的行按右侧行号排在最后
+    pub entries: Vec<DiffEntry>,
+}
+
+/// 比较两行的内容，相同时返回 `true`
+///
+/// 文本相同直接视为相同，否则按 JSON 值比较，忽略字段顺序与空白。
+fn same_row(left: &str, right: &str) -> bool {
+    if left == right {
+        return true;
+    }
+    match (serde_json::from_str::<JsonValue>(left), serde_json::from_str::<JsonValue>(right)) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => false,
+    }
+}
+
+/// 获取非空行，空行与超出范围的行视为不存在
+fn non_empty<'a>(lines: &'a MappedLines, line_num: usize) -> Option<&'a str> {
+    lines.line(line_num).filter(|line| !line.is_empty())
+}
+
+/// 报告比较进度
+fn report<F: Fn(LoadingProgress)>(progress_callback: &F, current: usize, total: usize, stage: &str) {
+    progress_callback(LoadingProgress {
+        current,
+        total,
+        stage: stage.to_string(),
+        percentage: if total > 0 { current as f32 / total as f32 * 100.0 } else { 100.0 },
+    });
+}
+
+impl FileDiff {
+    /// 比较两个文件
+    ///
+    /// `key_field` 为空时按行号逐行对齐，否则按该字段的值对齐；
+    /// 同一侧字段值重复的行按出现顺序依次配对。
+    pub fn compare<F>(left_path: &str, right_path: &str, key_field: Option<&str>, progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let key_path = key_field.filter(|field| !field.trim().is_empty()).map(JsonPath::parse).transpose()?;
+
+        report(&progress_callback, 0, 2, "读取文件行");
+        let left = DiffSide::open(left_path)?;
+        let right = DiffSide::open(right_path)?;
+        report(&progress_callback, 2, 2, "读取文件行");
+
+        let (summary, entries) = {
+            let left_lines = left.map()?;
+            let right_lines = right.map()?;
+            let mut summary = DiffSummary {
+                left_path: left_path.to_string(),
+                right_path: right_path.to_string(),
+                key_field: key_path.as_ref().map(|path| path.to_string()),
+                left_rows: 0,
+                right_rows: 0,
+                unchanged: 0,
+                added: 0,
+                removed: 0,
+                changed: 0,
+                left_missing_key: 0,
+                right_missing_key: 0,
+                duplicate_keys: 0,
+            };
+            let entries = match &key_path {
+                None => Self::compare_by_line(&left_lines, &right_lines, &mut summary, &progress_callback),
+                Some(path) => Self::compare_by_key(&left_lines, &right_lines, path, &mut summary, &progress_callback),
+            };
+            for entry in &entries {
+                match entry.kind {
+                    DiffKind::Added => summary.added += 1,
+                    DiffKind::Removed => summary.removed += 1,
+                    DiffKind::Changed => summary.changed += 1,
+                }
+            }
+            (summary, entries)
+        };
+
+        Ok(Self {
+            left,
+            right,
+            key_field: key_path,
+            summary,
+            entries,
+        })
+    }
+
+    /// 按行号对齐比较，空行视为不存在
+    fn compare_by_line<F>(left: &MappedLines, right: &MappedLines, summary: &mut DiffSummary, progress_callback: &F) -> Vec<DiffEntry>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let total = left.len().max(right.len());
+
+        let mut entries = Vec::new();
+        let mut start = 0;
+        while start < total {
+            let end = (start + BATCH_LINES).min(total);
+            let batch: Vec<(usize, usize, Option<DiffEntry>)> = (start..end)
+                .into_par_iter()
+                .map(|line_num| match (non_empty(left, line_num), non_empty(right, line_num)) {
+                    (Some(a), Some(b)) => {
+                        let entry = (!same_row(a, b)).then_some(DiffEntry {
+                            kind: DiffKind::Changed,
+                            left_line: Some(line_num),
+                            right_line: Some(line_num),
+                        });
+                        (1, 1, entry)
+                    }
+                    (Some(_), None) => (1, 0, Some(DiffEntry {
+                        kind: DiffKind::Removed,
+                        left_line: Some(line_num),
+                        right_line: None,
+                    })),
+                    (None, Some(_)) => (0, 1, Some(DiffEntry {
+                        kind: DiffKind::Added,
+                        left_line: None,
+                        right_line: Some(line_num),
+                    })),
+                    (None, None) => (0, 0, None),
+                })
+                .collect();
+
+            for (left_rows, right_rows, entry) in batch {
+                summary.left_rows += left_rows;
+                summary.right_rows += right_rows;
+                match entry {
+                    Some(entry) => entries.push(entry),
+                    None if left_rows > 0 => summary.unchanged += 1,
+                    None => {}
+                }
+            }
+            start = end;
+            report(progress_callback, start, total, "逐行比较");
+        }
+        entries
+    }
+
+    /// 提取每个非空行的对齐字段值的哈希，缺少字段或无法解析的行为 `None`
+    fn keys(lines: &MappedLines, path: &JsonPath) -> Vec<(usize, Option<u128>)> {
+        lines.par_filter_map(|line_num, line| {
+            let key = serde_json::from_str::<JsonValue>(line).ok().and_then(|row| {
+                let mut key = String::new();
+                canonical_json(path.get(&row)?, &mut key);
+                Some(hash128(&key))
+            });
+            Some((line_num, key))
+        })
+    }
+
+    /// 按字段值对齐比较
+    fn compare_by_key<F>(left: &MappedLines, right: &MappedLines, path: &JsonPath, summary: &mut DiffSummary, progress_callback: &F) -> Vec<DiffEntry>
+    where
+        F: Fn(LoadingProgress),
+    {
+        report(progress_callback, 0, 3, "提取对齐字段");
+        let left_keys = Self::keys(left, path);
+        let right_keys = Self::keys(right, path);
+        summary.left_rows = left_keys.len();
+        summary.right_rows = right_keys.len();
+        report(progress_callback, 1, 3, "提取对齐字段");
+
+        // 右侧每个字段值对应的行号，按出现顺序
+        let mut right_index: HashMap<u128, Vec<usize>> = HashMap::with_capacity(right_keys.len());
+        for &(line_num, key) in &right_keys {
+            match key {
+                Some(key) => {
+                    let lines = right_index.entry(key).or_default();
+                    if !lines.is_empty() {
+                        summary.duplicate_keys += 1;
+                    }
+                    lines.push(line_num);
+                }
+                None => summary.right_missing_key += 1,
+            }
+        }
+
+        // 左侧第 k 次出现的值与右侧第 k 次出现的值配对
+        let mut occurrences: HashMap<u128, usize> = HashMap::with_capacity(left_keys.len());
+        let mut pairs = Vec::new();
+        let mut removed = Vec::new();
+        let mut matched = HashSet::new();
+        for &(line_num, key) in &left_keys {
+            let Some(key) = key else {
+                summary.left_missing_key += 1;
+                continue;
+            };
+            let seen = occurrences.entry(key).or_insert(0);
+            if *seen > 0 {
+                summary.duplicate_keys += 1;
+            }
+            match right_index.get(&key).and_then(|lines| lines.get(*seen)) {
+                Some(&right_line) => {
+                    pairs.push((line_num, right_line));
+                    matched.insert(right_line);
+                }
+                None => removed.push(line_num),
+            }
+            *seen += 1;
+        }
+        report(progress_callback, 2, 3, "比较配对的行");
+
+        let changed: Vec<(usize, usize)> = pairs
+            .par_iter()
+            .filter(|&&(a, b)| match (left.line(a), right.line(b)) {
+                (Some(a), Some(b)) => !same_row(a, b),
+                _ => true,
+            })
+            .copied()
+            .collect();
+        summary.unchanged = pairs.len() - changed.len();
+
+        let mut entries: Vec<DiffEntry> = changed
+            .into_iter()
+            .map(|(a, b)| DiffEntry {
+                kind: DiffKind::Changed,
+                left_line: Some(a),
+                right_line: Some(b),
+            })
+            .chain(removed.into_iter().map(|line_num| DiffEntry {
+                kind: DiffKind::Removed,
+                left_line: Some(line_num),
+                right_line: None,
+            }))
+            .collect();
+        entries.sort_by_key(|entry| entry.left_line);
+        entries.extend(
+            right_keys
+                .iter()
+                .filter(|(line_num, key)| key.is_some() && !matched.contains(line_num))
+                .map(|&(line_num, _)| DiffEntry {
+                    kind: DiffKind::Added,
+                    left_line: None,
+                    right_line: Some(line_num),
+                }),
+        );
+        report(progress_callback, 3, 3, "比较配对的行");
+        entries
+    }
+
+    /// 筛选指定类型的差异
+    pub fn filtered(&self, kind: Option<DiffKind>) -> Vec<DiffEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| kind.is_none_or(|kind| entry.kind == kind))
+            .copied()
+            .collect()
+    }
+
+    /// 加载差异的详细内容
+    pub fn details(&self, entries: &[DiffEntry]) -> AppResult<Vec<RowDiff>> {
+        let left_lines = self.left.map()?;
+        let right_lines = self.right.map()?;
+        let parse = |lines: &MappedLines, line_num: Option<usize>| -> Option<JsonValue> {
+            let line = lines.line(line_num?)?;
+            Some(serde_json::from_str::<JsonValue>(line).unwrap_or_else(|_| JsonValue::String(line.to_string())))
+        };
+
+        Ok(entries
+            .par_iter()
+            .map(|entry| {
+                let left = parse(&left_lines, entry.left_line);
+                let right = parse(&right_lines, entry.right_line);
+                let key = self
+                    .key_field
+                    .as_ref()
+                    .and_then(|path| left.as_ref().or(right.as_ref()).and_then(|row| path.get(row)).cloned());
+
+                let (row, changes) = match entry.kind {
+                    DiffKind::Added => (right, Vec::new()),
+                    DiffKind::Removed => (left, Vec::new()),
+                    DiffKind::Changed => {
+                        let mut changes = Vec::new();
+                        if let (Some(left), Some(right)) = (&left, &right) {
+                            diff_values(left, right, &mut String::new(), &mut changes);
+                        }
+                        (None, changes)
+                    }
+                };
+
+                RowDiff {
+                    kind: entry.kind.name().to_string(),
+                    left_line: entry.left_line,
+                    right_line: entry.right_line,
+                    key,
+                    row,
+                    changes,
+                }
+            })
+            .collect())
+    }
+}
+
+/// 递归比较两个值，记录字段级差异
+///
+/// 对象按字段名比较，数组按下标比较，类型不同或标量不等时记为整体修改。
+/// 路径格式与 `JsonPath` 一致，例如 `messages[1].content`。
+pub fn diff_values(left: &JsonValue, right: &JsonValue, path: &mut String, changes: &mut Vec<FieldChange>) {
+    let len = path.len();
+    match (left, right) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys().filter(|key| !a.contains_key(*key))).collect();
+            keys.sort();
+            for key in keys {
+                push_key(path, key);
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_values(x, y, path, changes),
+                    (Some(x), None) => changes.push(FieldChange {
+                        path: path.clone(),
+                        kind: "removed".to_string(),
+                        left: Some(x.clone()),
+                        right: None,
+                        text_diff: None,
+                    }),
+                    (None, Some(y)) => changes.push(FieldChange {
+                        path: path.clone(),
+                        kind: "added".to_string(),
+                        left: None,
+                        right: Some(y.clone()),
+                        text_diff: None,
+                    }),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            for index in 0..a.len().max(b.len()) {
+                path.push_str(&format!("[{}]", index));
+                match (a.get(index), b.get(index)) {
+                    (Some(x), Some(y)) => diff_values(x, y, path, changes),
+                    (Some(x), None) => changes.push(FieldChange {
+                        path: path.clone(),
+                        kind: "removed".to_string(),
+                        left: Some(x.clone()),
+                        right: None,
+                        text_diff: None,
+                    }),
+                    (None, Some(y)) => changes.push(FieldChange {
+                        path: path.clone(),
+                        kind: "added".to_string(),
+                        left: None,
+                        right: Some(y.clone()),
+                        text_diff: None,
+                    }),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        _ if left != right => changes.push(FieldChange {
+            path: path.clone(),
+            kind: "changed".to_string(),
+            left: Some(left.clone()),
+            right: Some(right.clone()),
+            text_diff: None,
+        }),
+        _ => {}
+    }
+}
+
+/// 比较两行，较长的字符串附带字符级差异
+pub fn compare_rows(left: JsonValue, right: JsonValue) -> RowComparison {
+    let mut changes = Vec::new();
+    diff_values(&left, &right, &mut String::new(), &mut changes);
+
+    for change in &mut changes {
+        if let (Some(JsonValue::String(a)), Some(JsonValue::String(b))) = (&change.left, &change.right) {
+            let (a_len, b_len) = (a.chars().count(), b.chars().count());
+            if a_len.max(b_len) > LONG_STRING_CHARS && a_len + b_len <= MAX_TEXT_DIFF_CHARS {
+                change.text_diff = Some(text_diff(a, b));
+            }
+        }
+    }
+
+    RowComparison {
+        identical: changes.is_empty(),
+        left,
+        right,
+        changes,
+    }
+}
+
+/// 从任意文件中读取指定行（已去除首尾空白），逐行扫描到目标行
+pub fn read_file_line(path: &str, line_num: usize) -> AppResult<String> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut line = String::new();
+    for _ in 0..=line_num {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(AppError::General(format!("行号超出范围: {} 第 {} 行", path, line_num)));
+        }
+    }
+    Ok(line.trim().to_string())
+}
@@ -0,0 +1,168 @@
+use crate::jsonl::{EditJournal, FileFingerprint, Fnv64, JournalSnapshot};
+use crate::models::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 草稿格式版本，格式不兼容时递增
+const DRAFT_VERSION: u32 = 1;
+
+/// 连续编辑停止这么久之后才写入草稿
+const DRAFT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 尚未保存的编辑的草稿
+///
+/// 按文件路径保存在应用数据目录中，并记录编辑所基于的文件指纹，
+/// 文件在草稿保存后被修改时不能恢复。
+#[derive(Serialize, Deserialize)]
+pub struct EditDraft {
+    /// 草稿格式版本
+    version: u32,
+
+    /// 源文件路径
+    source: String,
+
+    /// 编辑所基于的文件指纹
+    fingerprint: FileFingerprint,
+
+    /// 保存时间（Unix 毫秒）
+    saved_at_ms: u64,
+
+    /// 编辑日志
+    journal: JournalSnapshot,
+}
+
+impl EditDraft {
+    /// 记录当前的编辑日志
+    pub fn new(source: &str, fingerprint: FileFingerprint, journal: &EditJournal) -> Self {
+        let saved_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self {
+            version: DRAFT_VERSION,
+            source: source.to_string(),
+            fingerprint,
+            saved_at_ms,
+            journal: journal.snapshot(),
+        }
+    }
+
+    /// 草稿文件路径，由源文件路径的哈希决定
+    pub fn draft_path(draft_dir: &Path, source: &str) -> PathBuf {
+        draft_dir.join(format!("{:016x}.draft.json", Fnv64::hash(source.as_bytes())))
+    }
+
+    /// 源文件路径
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 编辑所基于的文件指纹
+    pub fn fingerprint(&self) -> FileFingerprint {
+        self.fingerprint
+    }
+
+    /// 保存时间（Unix 毫秒）
+    pub fn saved_at_ms(&self) -> u64 {
+        self.saved_at_ms
+    }
+
+    /// 草稿中的编辑操作数
+    pub fn pending_edits(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// 由草稿重建编辑日志，`total_lines` 为源文件的行数
+    pub fn into_journal(self, total_lines: usize) -> EditJournal {
+        EditJournal::from_snapshot(self.journal, total_lines)
+    }
+
+    /// 写入磁盘，先写临时文件再替换，避免崩溃时留下损坏的草稿
+    pub fn save(&self, draft_path: &Path) -> AppResult<()> {
+        if let Some(parent) = draft_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let temp_path = draft_path.with_extension("json.tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&temp_path)?);
+            serde_json::to_writer(&mut writer, self)?;
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        std::fs::rename(&temp_path, draft_path)?;
+        Ok(())
+    }
+
+    /// 从磁盘读取草稿
+    pub fn load(draft_path: &Path) -> AppResult<Self> {
+        let reader = BufReader::new(File::open(draft_path)?);
+        let draft: Self = serde_json::from_reader(reader)
+            .map_err(|e| AppError::General(format!("草稿文件已损坏: {}", e)))?;
+        if draft.version != DRAFT_VERSION {
+            return Err(AppError::General(format!("不支持的草稿版本: {}", draft.version)));
+        }
+        Ok(draft)
+    }
+}
+
+/// 草稿写入的防抖调度
+///
+/// 编辑时只记录需要更新草稿的文件，由后台线程在编辑停止一段时间后写入一次，
+/// 避免每次编辑都重写并同步整个编辑日志。
+#[derive(Default)]
+pub struct DraftSchedule {
+    /// 等待写入草稿的源文件路径与到期时间
+    pending: Mutex<Option<(String, Instant)>>,
+
+    /// 有新的写入请求时唤醒后台线程
+    changed: Condvar,
+
+    /// 草稿文件的写入与删除互斥
+    io: Mutex<()>,
+}
+
+impl DraftSchedule {
+    /// 请求更新源文件的草稿，之前尚未到期的请求顺延
+    pub fn schedule(&self, source: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            *pending = Some((source.to_string(), Instant::now() + DRAFT_DEBOUNCE));
+            self.changed.notify_one();
+        }
+    }
+
+    /// 取走源文件尚未写入的请求，返回是否存在
+    pub fn take(&self, source: &str) -> bool {
+        let Ok(mut pending) = self.pending.lock() else {
+            return false;
+        };
+        if pending.as_ref().is_some_and(|(path, _)| path == source) {
+            *pending = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 阻塞到有请求到期，返回其源文件路径
+    pub fn wait_due(&self) -> Option<String> {
+        let mut pending = self.pending.lock().ok()?;
+        loop {
+            let due = pending.as_ref().map(|(_, due)| *due);
+            pending = match due {
+                Some(due) if due <= Instant::now() => return pending.take().map(|(path, _)| path),
+                Some(due) => self.changed.wait_timeout(pending, due - Instant::now()).ok()?.0,
+                None => self.changed.wait(pending).ok()?,
+            };
+        }
+    }
+
+    /// 获取草稿文件的读写锁
+    pub fn io_lock(&self) -> MutexGuard<'_, ()> {
+        self.io.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,868 @@
+use crate::jsonl::{line_matches, JsonPath, PathSegment, RowTransform};
+use crate::models::{AppError, AppResult, JsonValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 以程序文本的形式保存 jq 程序，读取时重新编译
+mod program_text {
+    use super::*;
+    use serde::de::Error;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(transform: &Arc<RowTransform>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(transform.program())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<RowTransform>, D::Error> {
+        let program = String::deserialize(deserializer)?;
+        RowTransform::compile(&program).map(Arc::new).map_err(D::Error::custom)
+    }
+}
+
+/// 可选 jq 程序的 `program_text`
+mod optional_program_text {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(transform: &Option<Arc<RowTransform>>, serializer: S) -> Result<S::Ok, S::Error> {
+        transform.as_ref().map(|transform| transform.program()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Arc<RowTransform>>, D::Error> {
+        #[derive(Deserialize)]
+        struct Program(#[serde(with = "program_text")] Arc<RowTransform>);
+        Ok(Option::<Program>::deserialize(deserializer)?.map(|program| program.0))
+    }
+}
+
+/// 行标识
+///
+/// 原文件中的行以原始行号标识，新插入（包括复制）的行以插入顺序编号，
+/// 增删行不会改变已有行的标识。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RowId {
+    /// 原文件中的行
+    Original(usize),
+
+    /// 新插入的行，对应 `EditJournal::inserted` 中的下标
+    Inserted(usize),
+}
+
+/// 新插入的行
+#[derive(Clone, Serialize, Deserialize)]
+struct InsertedRow {
+    /// 插入时的内容
+    content: JsonValue,
+
+    /// 插入该行的操作在日志中的下标，此前的操作不作用于该行
+    created_at: usize,
+}
+
+/// 批量添加字段时的取值
+#[derive(Clone, Serialize, Deserialize)]
+pub enum FieldValue {
+    /// 固定值
+    Literal(JsonValue),
+
+    /// 对每一行求值的 jq 表达式，例如 `.prompt | length`
+    Computed(#[serde(with = "program_text")] Arc<RowTransform>),
+}
+
+impl FieldValue {
+    /// 计算某一行的取值，求值失败时返回 `None`
+    fn evaluate(&self, row: &JsonValue) -> Option<JsonValue> {
+        match self {
+            Self::Literal(value) => Some(value.clone()),
+            Self::Computed(transform) => transform.apply(row.clone()).ok(),
+        }
+    }
+}
+
+/// 批量操作的行筛选条件，所有条件都满足的行才会被修改
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct RowFilter {
+    /// 行内容包含的关键词（已转为小写）
+    keyword: Option<String>,
+
+    /// jq 条件表达式，结果不为 false 或 null 时满足
+    #[serde(with = "optional_program_text")]
+    condition: Option<Arc<RowTransform>>,
+}
+
+impl RowFilter {
+    /// 创建筛选条件，空关键词视为不限制
+    pub fn new(keyword: Option<&str>, condition: Option<RowTransform>) -> Self {
+        Self {
+            keyword: keyword.filter(|k| !k.is_empty()).map(str::to_lowercase),
+            condition: condition.map(Arc::new),
+        }
+    }
+
+    /// 一行（应用此前的编辑后）是否满足条件
+    fn matches(&self, row: &JsonValue) -> bool {
+        if let Some(keyword) = &self.keyword {
+            if !line_matches(&row.to_string(), keyword) {
+                return false;
+            }
+        }
+        match &self.condition {
+            Some(condition) => !matches!(condition.apply(row.clone()), Ok(JsonValue::Null | JsonValue::Bool(false)) | Err(_)),
+            None => true,
+        }
+    }
+}
+
+/// 一次编辑操作
+#[derive(Clone, Serialize, Deserialize)]
+pub enum EditOp {
+    /// 设置某一行中指定路径的值
+    SetValue {
+        row: RowId,
+        path: JsonPath,
+        value: JsonValue,
+    },
+
+    /// 为满足条件的行添加字段，`overwrite` 为假时跳过已有该字段的行
+    AddField {
+        path: JsonPath,
+        value: FieldValue,
+        overwrite: bool,
+        filter: RowFilter,
+    },
+
+    /// 删除满足条件的行中的字段
+    RemoveField {
+        path: JsonPath,
+        filter: RowFilter,
+    },
+
+    /// 重命名或移动满足条件的行中的字段，目标字段已存在时被覆盖
+    RenameField {
+        from: JsonPath,
+        to: JsonPath,
+        filter: RowFilter,
+    },
+
+    /// 插入行，记录每一行插入后的位置（升序），`copied` 表示由复制产生
+    InsertRows {
+        rows: Vec<(usize, RowId)>,
+        copied: bool,
+    },
+
+    /// 删除行，记录每一行删除前的位置（升序）
+    DeleteRows {
+        rows: Vec<(usize, RowId)>,
+    },
+}
+
+impl EditOp {
+    /// 操作类型名称
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SetValue { .. } => "set_value",
+            Self::AddField { .. } => "add_field",
+            Self::RemoveField { .. } => "remove_field",
+            Self::RenameField { .. } => "rename_field",
+            Self::InsertRows { copied: false, .. } => "insert_rows",
+            Self::InsertRows { copied: true, .. } => "duplicate_rows",
+            Self::DeleteRows { .. } => "delete_rows",
+        }
+    }
+
+    /// 操作的简要说明
+    pub fn describe(&self) -> String {
+        match self {
+            Self::SetValue { row: RowId::Original(line_num), path, .. } => format!("修改第 {} 行的 {}", line_num, path),
+            Self::SetValue { row: RowId::Inserted(_), path, .. } => format!("修改新插入行的 {}", path),
+            Self::AddField { path, .. } => format!("添加字段 {}", path),
+            Self::RemoveField { path, .. } => format!("删除字段 {}", path),
+            Self::RenameField { from, to, .. } => format!("重命名字段 {} -> {}", from, to),
+            Self::InsertRows { rows, copied: false } => format!("插入 {} 行", rows.len()),
+            Self::InsertRows { rows, copied: true } => format!("复制 {} 行", rows.len()),
+            Self::DeleteRows { rows } => format!("删除 {} 行", rows.len()),
+        }
+    }
+
+    /// 是否改变行排列
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::InsertRows { .. } | Self::DeleteRows { .. })
+    }
+
+    /// 对一行的内容应用该操作
+    ///
+    /// 批量操作跳过不满足条件或无法修改的行，只有单行编辑会返回错误。
+    fn apply(&self, row: &mut JsonValue) -> AppResult<()> {
+        match self {
+            Self::SetValue { path, value, .. } => path.set(row, value.clone()),
+            Self::AddField { path, value, overwrite, filter } => {
+                if (*overwrite || path.get(row).is_none()) && filter.matches(row) {
+                    if let Some(value) = value.evaluate(row) {
+                        let _ = path.set(row, value);
+                    }
+                }
+                Ok(())
+            }
+            Self::RemoveField { path, filter } => {
+                if path.get(row).is_some() && filter.matches(row) {
+                    path.remove(row);
+                }
+                Ok(())
+            }
+            Self::RenameField { from, to, filter } => {
+                if from.get(row).is_some() && filter.matches(row) {
+                    if let Some(value) = from.remove(row) {
+                        // 目标位置无法写入时放回原处
+                        if to.set(row, value.clone()).is_err() {
+                            let _ = from.set(row, value);
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Self::InsertRows { .. } | Self::DeleteRows { .. } => Ok(()),
+        }
+    }
+}
+
+/// 在排列中按升序位置插入行，一次遍历完成
+fn insert_into(order: &mut Vec<RowId>, rows: &[(usize, RowId)]) {
+    let mut result = Vec::with_capacity(order.len() + rows.len());
+    let mut existing = order.drain(..);
+    for &(position, row) in rows {
+        while result.len() < position {
+            match existing.next() {
+                Some(id) => result.push(id),
+                None => break,
+            }
+        }
+        result.push(row);
+    }
+    result.extend(existing);
+    *order = result;
+}
+
+/// 从排列中删除升序位置上的行，一次遍历完成
+fn delete_from(order: &mut Vec<RowId>, rows: &[(usize, RowId)]) {
+    let mut removed = rows.iter().map(|(position, _)| *position).peekable();
+    let mut position = 0;
+    order.retain(|_| {
+        let keep = removed.peek() != Some(&position);
+        if !keep {
+            removed.next();
+        }
+        position += 1;
+        keep
+    });
+}
+
+/// 编辑日志中需要持久化的部分，行索引与行排列在恢复时重放操作得到
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JournalSnapshot {
+    /// 已执行的操作
+    ops: Vec<EditOp>,
+
+    /// 可以重做的操作
+    redo: Vec<EditOp>,
+
+    /// 新插入的行
+    inserted: Vec<InsertedRow>,
+}
+
+impl JournalSnapshot {
+    /// 已执行的操作数
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// 是否没有已执行的操作
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// 编辑日志
+///
+/// 编辑不直接写入文件，而是按顺序记录在日志中；读取某一行时
+/// 以文件中的原始内容为基础依次重放涉及该行的操作，保存前原文件保持不变。
+/// 批量操作只记录一次，在读取每一行时再求值，不保存逐行的副本，
+/// 因此撤销与重做只需移动日志末尾的操作。
+#[derive(Default)]
+pub struct EditJournal {
+    /// 全部操作，按执行顺序
+    ops: Vec<EditOp>,
+
+    /// 已撤销、可以重做的操作，最近撤销的在末尾
+    redo: Vec<EditOp>,
+
+    /// 每行涉及的单行操作在 `ops` 中的下标
+    row_ops: HashMap<RowId, Vec<usize>>,
+
+    /// 批量操作在 `ops` 中的下标
+    bulk_ops: Vec<usize>,
+
+    /// 新插入的行
+    inserted: Vec<InsertedRow>,
+
+    /// 增删行后的行排列，没有增删行时为空（即原文件的顺序）
+    order: Option<Vec<RowId>>,
+}
+
+impl EditJournal {
+    /// 是否没有待保存的编辑
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// 操作数量
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// 已执行的操作，按执行顺序
+    pub fn ops(&self) -> &[EditOp] {
+        &self.ops
+    }
+
+    /// 可以重做的操作，按重做顺序
+    pub fn redo_ops(&self) -> impl Iterator<Item = &EditOp> {
+        self.redo.iter().rev()
+    }
+
+    /// 是否可以撤销
+    pub fn can_undo(&self) -> bool {
+        !self.ops.is_empty()
+    }
+
+    /// 是否可以重做
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// 被单行编辑修改过的行数
+    pub fn edited_rows(&self) -> usize {
+        self.row_ops.len()
+    }
+
+    /// 批量操作数量
+    pub fn bulk_edits(&self) -> usize {
+        self.bulk_ops.len()
+    }
+
+    /// 是否有尚未保存的增删行
+    pub fn has_row_changes(&self) -> bool {
+        self.order.is_some()
+    }
+
+    /// 当前的行数，`total_lines` 为原文件的行数
+    pub fn row_count(&self, total_lines: usize) -> usize {
+        self.order.as_ref().map_or(total_lines, Vec::len)
+    }
+
+    /// 指定位置上的行
+    pub fn row_at(&self, position: usize, total_lines: usize) -> Option<RowId> {
+        match &self.order {
+            Some(order) => order.get(position).copied(),
+            None => (position < total_lines).then_some(RowId::Original(position)),
+        }
+    }
+
+    /// 当前的行排列，没有增删行时为空
+    pub fn order(&self) -> Option<&[RowId]> {
+        self.order.as_deref()
+    }
+
+    /// 新插入的行数（不含插入后又被删除的行）
+    pub fn inserted_rows(&self) -> usize {
+        self.order.as_ref().map_or(0, |order| {
+            order.iter().filter(|row| matches!(row, RowId::Inserted(_))).count()
+        })
+    }
+
+    /// 原文件中被删除的行数
+    pub fn deleted_rows(&self, total_lines: usize) -> usize {
+        self.order.as_ref().map_or(0, |order| total_lines + self.inserted_rows() - order.len())
+    }
+
+    /// 某一行是否可能被编辑过（存在批量操作时每一行都需要重放，新插入的行总是需要重放）
+    pub fn is_edited(&self, row_id: RowId) -> bool {
+        matches!(row_id, RowId::Inserted(_)) || !self.bulk_ops.is_empty() || self.row_ops.contains_key(&row_id)
+    }
+
+    /// 新插入的行在插入时的内容
+    pub fn inserted_content(&self, index: usize) -> Option<&JsonValue> {
+        self.inserted.get(index).map(|row| &row.content)
+    }
+
+    /// 对一行的内容按执行顺序重放涉及该行的全部操作
+    ///
+    /// 原文件中的行从原始内容开始重放，新插入的行从插入时的内容开始重放。
+    pub fn apply(&self, row_id: RowId, row: &mut JsonValue) -> AppResult<()> {
+        let row_ops = self.row_ops.get(&row_id).map(Vec::as_slice).unwrap_or(&[]);
+        let bulk_start = match row_id {
+            RowId::Original(_) => 0,
+            RowId::Inserted(index) => {
+                let created_at = self.inserted.get(index).map_or(0, |row| row.created_at);
+                self.bulk_ops.partition_point(|&op| op < created_at)
+            }
+        };
+        let bulk_ops = &self.bulk_ops[bulk_start..];
+        let (mut i, mut j) = (0, 0);
+        while i < row_ops.len() || j < bulk_ops.len() {
+            let index = if j >= bulk_ops.len() || (i < row_ops.len() && row_ops[i] < bulk_ops[j]) {
+                i += 1;
+                row_ops[i - 1]
+            } else {
+                j += 1;
+                bulk_ops[j - 1]
+            };
+            self.ops[index].apply(row)?;
+        }
+        Ok(())
+    }
+
+    /// 解析一行原始内容并应用编辑，行不是 JSON 对象时返回 `None`
+    pub fn edited_row(&self, line_num: usize, line: &str) -> Option<JsonValue> {
+        let mut row = serde_json::from_str::<JsonValue>(line).ok().filter(JsonValue::is_object)?;
+        self.apply(RowId::Original(line_num), &mut row).ok()?;
+        Some(row)
+    }
+
+    /// 设置某一行中指定路径的值
+    ///
+    /// `row` 为该行当前（已应用此前编辑）的内容，设置成功后更新为新内容并记录操作。
+    pub fn set_value(&mut self, row_id: RowId, row: &mut JsonValue, path: JsonPath, value: JsonValue) -> AppResult<()> {
+        if path.is_empty() {
+            return Err(AppError::General("字段路径不能为空".to_string()));
+        }
+        let op = EditOp::SetValue {
+            row: row_id,
+            path,
+            value,
+        };
+        op.apply(row)?;
+        self.push(op);
+        Ok(())
+    }
+
+    /// 为满足条件的所有行添加字段
+    pub fn add_field(&mut self, path: JsonPath, value: FieldValue, overwrite: bool, filter: RowFilter) -> AppResult<()> {
+        if path.is_empty() {
+            return Err(AppError::General("字段路径不能为空".to_string()));
+        }
+        self.push(EditOp::AddField { path, value, overwrite, filter });
+        Ok(())
+    }
+
+    /// 删除满足条件的所有行中的字段
+    pub fn remove_field(&mut self, path: JsonPath, filter: RowFilter) -> AppResult<()> {
+        if path.is_empty() {
+            return Err(AppError::General("字段路径不能为空".to_string()));
+        }
+        self.push(EditOp::RemoveField { path, filter });
+        Ok(())
+    }
+
+    /// 重命名或移动满足条件的所有行中的字段
+    ///
+    /// 两个路径的最后一段都必须是字段名，目标路径不能位于源路径之下。
+    pub fn rename_field(&mut self, from: JsonPath, to: JsonPath, filter: RowFilter) -> AppResult<()> {
+        let ends_with_key = |path: &JsonPath| matches!(path.segments().last(), Some(PathSegment::Key(_)));
+        if !ends_with_key(&from) || !ends_with_key(&to) {
+            return Err(AppError::General("重命名的源路径和目标路径都必须以字段名结尾".to_string()));
+        }
+        if from == to {
+            return Err(AppError::General("源路径与目标路径相同".to_string()));
+        }
+        if to.segments().starts_with(from.segments()) {
+            return Err(AppError::General("目标路径不能位于源路径之下".to_string()));
+        }
+        self.push(EditOp::RenameField { from, to, filter });
+        Ok(())
+    }
+
+    /// 在指定位置插入若干行，`position` 等于行数时追加到末尾
+    pub fn insert_rows(&mut self, position: usize, rows: Vec<JsonValue>, total_lines: usize) -> AppResult<()> {
+        if position > self.row_count(total_lines) {
+            return Err(AppError::General(format!("插入位置超出范围: {}", position)));
+        }
+        if rows.is_empty() {
+            return Err(AppError::General("没有要插入的行".to_string()));
+        }
+        if rows.iter().any(|row| !row.is_object()) {
+            return Err(AppError::General("插入的行必须是 JSON 对象".to_string()));
+        }
+
+        self.discard_redo();
+        let created_at = self.ops.len();
+        let rows = rows
+            .into_iter()
+            .enumerate()
+            .map(|(offset, content)| {
+                self.inserted.push(InsertedRow { content, created_at });
+                (position + offset, RowId::Inserted(self.inserted.len() - 1))
+            })
+            .collect();
+        self.init_order(total_lines);
+        self.push(EditOp::InsertRows { rows, copied: false });
+        Ok(())
+    }
+
+    /// 复制若干行，每个副本紧跟在原行之后
+    ///
+    /// `rows` 为 `(位置, 当前内容)`，位置按删除重复后升序处理。
+    pub fn duplicate_rows(&mut self, mut rows: Vec<(usize, JsonValue)>, total_lines: usize) -> AppResult<()> {
+        rows.sort_by_key(|(position, _)| *position);
+        rows.dedup_by_key(|(position, _)| *position);
+        let count = self.row_count(total_lines);
+        if rows.iter().any(|(position, _)| *position >= count) {
+            return Err(AppError::General("行号超出范围".to_string()));
+        }
+        if rows.is_empty() {
+            return Err(AppError::General("没有要复制的行".to_string()));
+        }
+
+        self.discard_redo();
+        let created_at = self.ops.len();
+        let rows = rows
+            .into_iter()
+            .enumerate()
+            .map(|(offset, (position, content))| {
+                self.inserted.push(InsertedRow { content, created_at });
+                // 前面每复制一行，后面的位置都后移一行
+                (position + offset + 1, RowId::Inserted(self.inserted.len() - 1))
+            })
+            .collect();
+        self.init_order(total_lines);
+        self.push(EditOp::InsertRows { rows, copied: true });
+        Ok(())
+    }
+
+    /// 删除指定位置上的行，返回删除的行数
+    pub fn delete_rows(&mut self, positions: &[usize], total_lines: usize) -> AppResult<usize> {
+        let mut positions = positions.to_vec();
+        positions.sort_unstable();
+        positions.dedup();
+        let count = self.row_count(total_lines);
+        if positions.last().is_some_and(|&position| position >= count) {
+            return Err(AppError::General("行号超出范围".to_string()));
+        }
+        if positions.is_empty() {
+            return Err(AppError::General("没有要删除的行".to_string()));
+        }
+
+        let rows: Vec<(usize, RowId)> = positions
+            .into_iter()
+            .filter_map(|position| self.row_at(position, total_lines).map(|row| (position, row)))
+            .collect();
+        let deleted = rows.len();
+        self.init_order(total_lines);
+        self.push(EditOp::DeleteRows { rows });
+        Ok(deleted)
+    }
+
+    /// 首次增删行前以原文件的顺序初始化行排列
+    fn init_order(&mut self, total_lines: usize) {
+        self.order.get_or_insert_with(|| (0..total_lines).map(RowId::Original).collect());
+    }
+
+    /// 记录一个新操作，此前撤销的操作不能再重做
+    fn push(&mut self, op: EditOp) {
+        self.discard_redo();
+        self.execute(op);
+    }
+
+    /// 执行一个操作并更新行索引与行排列
+    fn execute(&mut self, op: EditOp) {
+        let index = self.ops.len();
+        match &op {
+            EditOp::SetValue { row, .. } => self.row_ops.entry(*row).or_default().push(index),
+            EditOp::InsertRows { rows, .. } => insert_into(self.order.get_or_insert_with(Vec::new), rows),
+            EditOp::DeleteRows { rows } => delete_from(self.order.get_or_insert_with(Vec::new), rows),
+            _ => self.bulk_ops.push(index),
+        }
+        self.ops.push(op);
+    }
+
+    /// 丢弃可以重做的操作，以及只被这些操作引用的新插入行
+    fn discard_redo(&mut self) {
+        // 插入行按顺序编号，被撤销的插入总是位于末尾
+        let first_unused = self
+            .redo
+            .iter()
+            .filter_map(|op| match op {
+                EditOp::InsertRows { rows, .. } => rows.iter().find_map(|(_, row)| match row {
+                    RowId::Inserted(index) => Some(*index),
+                    RowId::Original(_) => None,
+                }),
+                _ => None,
+            })
+            .min();
+        if let Some(first_unused) = first_unused {
+            self.inserted.truncate(first_unused);
+        }
+        self.redo.clear();
+    }
+
+    /// 撤销最近的一个操作，返回被撤销的操作
+    pub fn undo(&mut self) -> Option<&EditOp> {
+        let op = self.ops.pop()?;
+        let index = self.ops.len();
+        match &op {
+            EditOp::SetValue { row, .. } => {
+                if let Some(ops) = self.row_ops.get_mut(row) {
+                    ops.retain(|&i| i != index);
+                    if ops.is_empty() {
+                        self.row_ops.remove(row);
+                    }
+                }
+            }
+            // 插入记录的是插入后的位置，删除记录的是删除前的位置，反向执行即可还原
+            EditOp::InsertRows { rows, .. } => {
+                if let Some(order) = &mut self.order {
+                    delete_from(order, rows);
+                }
+            }
+            EditOp::DeleteRows { rows } => {
+                if let Some(order) = &mut self.order {
+                    insert_into(order, rows);
+                }
+            }
+            _ => {
+                self.bulk_ops.pop();
+            }
+        }
+        // 撤销了全部增删行后恢复原文件的顺序
+        if op.is_structural() && !self.ops.iter().any(EditOp::is_structural) {
+            self.order = None;
+        }
+        self.redo.push(op);
+        self.redo.last()
+    }
+
+    /// 重做最近撤销的一个操作，返回被重做的操作
+    pub fn redo(&mut self, total_lines: usize) -> Option<&EditOp> {
+        let op = self.redo.pop()?;
+        if op.is_structural() {
+            self.init_order(total_lines);
+        }
+        self.execute(op);
+        self.ops.last()
+    }
+
+    /// 导出需要持久化的内容
+    pub fn snapshot(&self) -> JournalSnapshot {
+        JournalSnapshot {
+            ops: self.ops.clone(),
+            redo: self.redo.clone(),
+            inserted: self.inserted.clone(),
+        }
+    }
+
+    /// 由持久化的内容重建编辑日志，`total_lines` 为原文件的行数
+    pub fn from_snapshot(snapshot: JournalSnapshot, total_lines: usize) -> Self {
+        let mut journal = Self {
+            inserted: snapshot.inserted,
+            ..Self::default()
+        };
+        for op in snapshot.ops {
+            if op.is_structural() {
+                journal.init_order(total_lines);
+            }
+            journal.execute(op);
+        }
+        journal.redo = snapshot.redo;
+        journal
+    }
+
+    /// 把编辑移植到文件的新内容上，返回新的编辑日志与被丢弃的操作数
+    ///
+    /// 按行号记录的字段修改通过 `locate` 对应到新内容中内容相同的行，找不到对应行时丢弃；
+    /// 批量操作按条件对新内容重新求值；增删行以及新插入行上的修改无法对应到新内容，予以丢弃。
+    /// `read_row` 读取新内容中的一行，行不存在或修改无法应用时该修改同样被丢弃。
+    pub fn rebase<L, F>(&self, total_lines: usize, locate: L, read_row: F) -> (Self, usize)
+    where
+        L: Fn(usize) -> Option<usize>,
+        F: Fn(usize) -> Option<JsonValue>,
+    {
+        let mut journal = Self::default();
+        let mut dropped = 0;
+        for op in &self.ops {
+            let rebased = match op {
+                EditOp::SetValue { row: RowId::Original(line_num), path, value } => {
+                    locate(*line_num).filter(|&new_line| new_line < total_lines).filter(|&new_line| {
+                        read_row(new_line).filter(JsonValue::is_object).is_some_and(|mut row| {
+                            journal.apply(RowId::Original(new_line), &mut row).is_ok() && path.set(&mut row, value.clone()).is_ok()
+                        })
+                    }).map(|new_line| EditOp::SetValue {
+                        row: RowId::Original(new_line),
+                        path: path.clone(),
+                        value: value.clone(),
+                    })
+                }
+                EditOp::AddField { .. } | EditOp::RemoveField { .. } | EditOp::RenameField { .. } => Some(op.clone()),
+                _ => None,
+            };
+            match rebased {
+                Some(op) => journal.execute(op),
+                None => dropped += 1,
+            }
+        }
+        (journal, dropped)
+    }
+
+    /// 丢弃全部编辑
+    pub fn clear(&mut self) {
+        self.redo.clear();
+        self.ops.clear();
+        self.row_ops.clear();
+        self.bulk_ops.clear();
+        self.inserted.clear();
+        self.order = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use RowId::{Inserted, Original};
+
+    /// 原文件共三行
+    const TOTAL_LINES: usize = 3;
+
+    fn base(line_num: usize) -> JsonValue {
+        json!({ "id": line_num })
+    }
+
+    /// 当前的行排列
+    fn rows(journal: &EditJournal) -> Vec<RowId> {
+        (0..journal.row_count(TOTAL_LINES))
+            .map(|position| journal.row_at(position, TOTAL_LINES).unwrap())
+            .collect()
+    }
+
+    /// 按当前的行排列读取应用编辑后的全部行
+    fn contents(journal: &EditJournal) -> Vec<JsonValue> {
+        rows(journal)
+            .into_iter()
+            .map(|row_id| {
+                let mut row = match row_id {
+                    Original(line_num) => base(line_num),
+                    Inserted(index) => journal.inserted_content(index).cloned().unwrap(),
+                };
+                journal.apply(row_id, &mut row).unwrap();
+                row
+            })
+            .collect()
+    }
+
+    fn set_value(journal: &mut EditJournal, row_id: RowId, path: &str, value: JsonValue) {
+        let mut row = match row_id {
+            Original(line_num) => base(line_num),
+            Inserted(index) => journal.inserted_content(index).cloned().unwrap(),
+        };
+        journal.apply(row_id, &mut row).unwrap();
+        journal.set_value(row_id, &mut row, JsonPath::parse(path).unwrap(), value).unwrap();
+    }
+
+    #[test]
+    fn undo_insert_restores_original_order() {
+        let mut journal = EditJournal::default();
+        journal.insert_rows(1, vec![json!({ "id": "a" }), json!({ "id": "b" })], TOTAL_LINES).unwrap();
+        assert_eq!(rows(&journal), vec![Original(0), Inserted(0), Inserted(1), Original(1), Original(2)]);
+
+        assert!(matches!(journal.undo(), Some(EditOp::InsertRows { .. })));
+        assert!(!journal.has_row_changes());
+        assert_eq!(rows(&journal), vec![Original(0), Original(1), Original(2)]);
+        assert!(journal.can_redo());
+    }
+
+    #[test]
+    fn undo_duplicate_removes_copies() {
+        let mut journal = EditJournal::default();
+        journal.duplicate_rows(vec![(2, base(2)), (0, base(0))], TOTAL_LINES).unwrap();
+        assert_eq!(rows(&journal), vec![Original(0), Inserted(0), Original(1), Original(2), Inserted(1)]);
+        assert_eq!(contents(&journal)[4], base(2));
+
+        journal.undo();
+        assert_eq!(rows(&journal), vec![Original(0), Original(1), Original(2)]);
+    }
+
+    #[test]
+    fn undo_delete_restores_rows_at_their_positions() {
+        let mut journal = EditJournal::default();
+        journal.insert_rows(1, vec![json!({ "id": "a" })], TOTAL_LINES).unwrap();
+        assert_eq!(journal.delete_rows(&[3, 0, 1], TOTAL_LINES).unwrap(), 3);
+        assert_eq!(rows(&journal), vec![Original(1)]);
+        assert_eq!(journal.deleted_rows(TOTAL_LINES), 2);
+
+        journal.undo();
+        assert_eq!(rows(&journal), vec![Original(0), Inserted(0), Original(1), Original(2)]);
+        journal.undo();
+        assert_eq!(rows(&journal), vec![Original(0), Original(1), Original(2)]);
+        assert!(!journal.can_undo());
+    }
+
+    #[test]
+    fn redo_replays_undone_operations() {
+        let mut journal = EditJournal::default();
+        set_value(&mut journal, Original(1), "name", json!("x"));
+        journal.insert_rows(0, vec![json!({ "id": "a" })], TOTAL_LINES).unwrap();
+        journal.delete_rows(&[2], TOTAL_LINES).unwrap();
+        let expected = contents(&journal);
+
+        while journal.undo().is_some() {}
+        assert_eq!(contents(&journal), vec![base(0), base(1), base(2)]);
+
+        while journal.redo(TOTAL_LINES).is_some() {}
+        assert_eq!(contents(&journal), expected);
+        assert!(!journal.can_redo());
+    }
+
+    #[test]
+    fn new_operation_discards_redo_and_unused_inserted_rows() {
+        let mut journal = EditJournal::default();
+        journal.insert_rows(0, vec![json!({ "id": "a" })], TOTAL_LINES).unwrap();
+        journal.insert_rows(0, vec![json!({ "id": "b" })], TOTAL_LINES).unwrap();
+        journal.undo();
+
+        set_value(&mut journal, Original(0), "name", json!("x"));
+        assert!(!journal.can_redo());
+        assert!(journal.inserted_content(1).is_none());
+
+        // 被丢弃的插入行的编号可以重新使用
+        journal.insert_rows(0, vec![json!({ "id": "c" })], TOTAL_LINES).unwrap();
+        assert_eq!(journal.row_at(0, TOTAL_LINES), Some(Inserted(1)));
+        assert_eq!(journal.inserted_content(1), Some(&json!({ "id": "c" })));
+    }
+
+    #[test]
+    fn snapshot_round_trip_preserves_journal() {
+        let mut journal = EditJournal::default();
+        set_value(&mut journal, Original(1), "name", json!("x"));
+        journal.add_field(JsonPath::parse("tag").unwrap(), FieldValue::Literal(json!(true)), false, RowFilter::default()).unwrap();
+        journal.duplicate_rows(vec![(1, contents(&journal)[1].clone())], TOTAL_LINES).unwrap();
+        set_value(&mut journal, Inserted(0), "name", json!("copy"));
+        journal.delete_rows(&[0], TOTAL_LINES).unwrap();
+        journal.insert_rows(3, vec![json!({ "id": "z" })], TOTAL_LINES).unwrap();
+        journal.undo();
+
+        let text = serde_json::to_string(&journal.snapshot()).unwrap();
+        let snapshot: JournalSnapshot = serde_json::from_str(&text).unwrap();
+        assert_eq!(snapshot.len(), journal.len());
+        let mut restored = EditJournal::from_snapshot(snapshot, TOTAL_LINES);
+
+        assert_eq!(rows(&restored), rows(&journal));
+        assert_eq!(contents(&restored), contents(&journal));
+        assert_eq!(contents(&restored)[1], json!({ "id": 1, "name": "copy", "tag": true }));
+        assert_eq!(restored.edited_rows(), journal.edited_rows());
+        assert_eq!(restored.bulk_edits(), 1);
+
+        // 恢复后仍可重做与撤销
+        assert!(matches!(restored.redo(TOTAL_LINES), Some(EditOp::InsertRows { .. })));
+        assert_eq!(restored.row_at(3, TOTAL_LINES), Some(Inserted(1)));
+        while restored.undo().is_some() {}
+        assert_eq!(contents(&restored), vec![base(0), base(1), base(2)]);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::models::AppResult;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// 首尾采样的字节数
+const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// 文件指纹
+///
+/// 由文件大小、修改时间以及首尾各 64KB 内容的哈希组成，
+/// 用于判断索引、草稿等派生数据是否仍与文件一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileFingerprint {
+    /// 文件大小（字节）
+    pub size: u64,
+
+    /// 修改时间（Unix 毫秒）
+    pub modified_ms: u64,
+
+    /// 首尾采样内容的哈希
+    pub sample_hash: u64,
+}
+
+impl FileFingerprint {
+    /// 计算文件指纹
+    pub fn of<P: AsRef<Path>>(path: P) -> AppResult<Self> {
+        Self::of_file(&mut File::open(path.as_ref())?)
+    }
+
+    /// 计算已打开文件的指纹
+    ///
+    /// 文件被其他程序以替换的方式修改后，已打开的句柄仍指向原来的内容。
+    pub fn of_file(file: &mut File) -> AppResult<Self> {
+        let metadata = file.metadata()?;
+        let size = metadata.len();
+        let modified_ms = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let mut hasher = Fnv64::new();
+        let mut buffer = vec![0u8; SAMPLE_SIZE as usize];
+
+        // 文件头部
+        file.seek(SeekFrom::Start(0))?;
+        let head = file.read(&mut buffer)?;
+        hasher.write(&buffer[..head]);
+
+        // 文件尾部
+        if size > SAMPLE_SIZE * 2 {
+            file.seek(SeekFrom::Start(size - SAMPLE_SIZE))?;
+            file.read_exact(&mut buffer)?;
+            hasher.write(&buffer);
+        }
+
+        Ok(Self {
+            size,
+            modified_ms,
+            sample_hash: hasher.finish(),
+        })
+    }
+}
+
+/// FNV-1a 64 位哈希
+///
+/// 结果跨版本、跨平台稳定，可以安全地写入磁盘。
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv64(u64);
+
+impl Fnv64 {
+    /// 创建新的哈希器
+    pub fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    /// 写入字节
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// 获取哈希值
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+
+    /// 计算一段字节的哈希
+    pub fn hash(bytes: &[u8]) -> u64 {
+        let mut hasher = Self::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+}
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
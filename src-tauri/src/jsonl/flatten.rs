@@ -0,0 +1,181 @@
+use crate::jsonl::{push_key, SchemaNode};
+use crate::models::{AppError, AppResult, JsonObject, JsonValue, SchemaReport};
+use std::collections::HashSet;
+
+/// 数组的展开方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayMode {
+    /// 数组整体作为一列
+    Keep,
+
+    /// 按下标展开为 `tags[0]`、`tags[1]` 等列
+    Index,
+
+    /// 只含标量的数组拼接为一个字符串，含对象或数组的元素时整体作为一列
+    Join,
+}
+
+impl ArrayMode {
+    /// 从前端参数解析
+    pub fn parse(mode: &str) -> AppResult<Self> {
+        match mode {
+            "keep" => Ok(Self::Keep),
+            "index" => Ok(Self::Index),
+            "join" => Ok(Self::Join),
+            other => Err(AppError::General(format!("未知的数组展开方式: {}", other))),
+        }
+    }
+}
+
+/// 展开嵌套字段的选项
+#[derive(Debug, Clone)]
+pub struct FlattenOptions {
+    /// 最多展开的层数，0 表示不展开；更深的对象和数组整体作为一列
+    pub max_depth: usize,
+
+    /// 数组的展开方式
+    pub arrays: ArrayMode,
+
+    /// 按下标展开时每个数组最多展开的元素数，超出的元素不显示
+    pub max_array_items: usize,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            arrays: ArrayMode::Index,
+            max_array_items: 5,
+        }
+    }
+}
+
+/// 拼接数组元素时使用的分隔符
+const JOIN_SEPARATOR: &str = ", ";
+
+/// 将一行展开为以点号路径为键的对象，例如 `address.city`、`tags[0]`
+///
+/// 列名是合法的字段路径，可直接用于 `JsonPath::parse`。空对象和空数组保留为一列。
+pub fn flatten_row(row: JsonObject, options: &FlattenOptions) -> JsonObject {
+    let mut flat = JsonObject::with_capacity(row.len());
+    let mut path = String::new();
+    for (key, value) in row {
+        push_key(&mut path, &key);
+        flatten_value(value, &mut path, 0, options, &mut flat);
+        path.clear();
+    }
+    flat
+}
+
+/// 递归展开一个值
+fn flatten_value(value: JsonValue, path: &mut String, depth: usize, options: &FlattenOptions, flat: &mut JsonObject) {
+    let len = path.len();
+    match value {
+        JsonValue::Object(map) if depth < options.max_depth && !map.is_empty() => {
+            for (key, item) in map {
+                push_key(path, &key);
+                flatten_value(item, path, depth + 1, options, flat);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Array(items) if depth < options.max_depth && !items.is_empty() => match options.arrays {
+            ArrayMode::Index => {
+                for (index, item) in items.into_iter().take(options.max_array_items).enumerate() {
+                    path.push_str(&format!("[{}]", index));
+                    flatten_value(item, path, depth + 1, options, flat);
+                    path.truncate(len);
+                }
+            }
+            ArrayMode::Join if items.iter().all(|item| !item.is_object() && !item.is_array()) => {
+                let joined: Vec<String> = items
+                    .iter()
+                    .map(|item| match item {
+                        JsonValue::String(text) => text.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect();
+                flat.insert(path.clone(), JsonValue::String(joined.join(JOIN_SEPARATOR)));
+            }
+            _ => {
+                flat.insert(path.clone(), JsonValue::Array(items));
+            }
+        },
+        other => {
+            flat.insert(path.clone(), other);
+        }
+    }
+}
+
+/// 根据推断出的结构生成展开后的列名
+///
+/// 顶层字段沿用 `report.columns` 的顺序，嵌套字段按路径排序紧跟在所属字段之后；
+/// 按下标展开的数组按观测到的最大长度生成列。
+pub fn flat_columns(report: &SchemaReport, options: &FlattenOptions) -> Vec<String> {
+    let root = SchemaNode::from_report(report);
+    let mut columns = Vec::new();
+    let mut path = String::new();
+    for name in &report.columns {
+        if let Some((_, node)) = root.properties.iter().find(|(key, _)| key == name) {
+            push_key(&mut path, name);
+            collect_columns(node, &mut path, 0, options, &mut columns);
+            path.clear();
+        }
+    }
+    columns
+}
+
+/// 递归收集一个节点展开后的列
+fn collect_columns(node: &SchemaNode, path: &mut String, depth: usize, options: &FlattenOptions, columns: &mut Vec<String>) {
+    let expand_object = depth < options.max_depth && !node.properties.is_empty();
+    let array_items = match (&node.items, options.arrays) {
+        (Some(items), ArrayMode::Index) if depth < options.max_depth => {
+            let count = node.field.and_then(|field| field.max_items).unwrap_or(0).min(options.max_array_items);
+            Some((items, count)).filter(|_| count > 0)
+        }
+        _ => None,
+    };
+
+    // 标量以及未展开的对象或数组各占一列
+    let is_leaf = match node.field {
+        Some(field) => field.types.iter().any(|t| match t.name.as_str() {
+            "object" => !expand_object,
+            "array" => array_items.is_none(),
+            _ => true,
+        }),
+        None => !expand_object && array_items.is_none(),
+    };
+    if is_leaf {
+        columns.push(path.clone());
+    }
+
+    let len = path.len();
+    if expand_object {
+        for (key, child) in &node.properties {
+            push_key(path, key);
+            collect_columns(child, path, depth + 1, options, columns);
+            path.truncate(len);
+        }
+    }
+    if let Some((items, count)) = array_items {
+        for index in 0..count {
+            path.push_str(&format!("[{}]", index));
+            collect_columns(items, path, depth + 1, options, columns);
+            path.truncate(len);
+        }
+    }
+}
+
+/// 在已有列之后补充展开数据中出现但不在列表中的列（按名称排序）
+pub fn extend_columns(columns: &mut Vec<String>, rows: &[JsonObject]) {
+    let known: HashSet<&str> = columns.iter().map(String::as_str).collect();
+    let mut extra: Vec<String> = rows
+        .iter()
+        .flat_map(|row| row.keys())
+        .filter(|key| !known.contains(key.as_str()))
+        .cloned()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    extra.sort();
+    columns.extend(extra);
+}
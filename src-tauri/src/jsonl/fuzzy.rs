@@ -0,0 +1,154 @@
+use crate::jsonl::{is_cjk, tokenize, tokenize_value, MappedLines};
+use crate::models::{AppError, AppResult, JsonValue};
+use std::collections::HashSet;
+
+/// 单个查询词允许的最大编辑距离上限
+const MAX_DISTANCE_LIMIT: usize = 3;
+
+/// 模糊关键词匹配器
+///
+/// 查询按与索引相同的规则切分为词；每个词需要在行内某个词元中近似出现
+/// （编辑距离不超过阈值的子串），全部词都命中的行才算匹配。
+pub struct FuzzyMatcher {
+    /// 查询词（小写字符序列）
+    terms: Vec<Vec<char>>,
+
+    /// 每个查询词允许的最大编辑距离
+    max_distances: Vec<usize>,
+}
+
+impl FuzzyMatcher {
+    /// 创建匹配器，未指定距离时按词长自动选择
+    pub fn new(query: &str, max_distance: Option<usize>) -> AppResult<Self> {
+        let mut seen = HashSet::new();
+        let terms: Vec<Vec<char>> = tokenize(query)
+            .into_iter()
+            .filter(|term| seen.insert(term.clone()))
+            .map(|term| term.chars().collect())
+            .collect();
+
+        if terms.is_empty() {
+            return Err(AppError::General("模糊搜索关键词为空".to_string()));
+        }
+
+        let max_distances = terms
+            .iter()
+            .map(|term| match max_distance {
+                Some(distance) => distance.min(MAX_DISTANCE_LIMIT).min(term.len().saturating_sub(1)),
+                None => Self::auto_distance(term),
+            })
+            .collect();
+
+        Ok(Self { terms, max_distances })
+    }
+
+    /// 按词长选择默认的编辑距离
+    fn auto_distance(term: &[char]) -> usize {
+        // 中日韩文字单字信息量大，较短的词就允许一处差异
+        let (one, two) = if term.iter().any(|&c| is_cjk(c)) { (2, 5) } else { (4, 8) };
+        if term.len() >= two {
+            2
+        } else if term.len() >= one {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// 计算一行的匹配得分，未匹配时返回 `None`
+    ///
+    /// 得分在 0 到 1 之间：完整词元命中为 1，子串命中为 0.9，近似命中按距离递减。
+    pub fn score_line(&self, line: &str) -> Option<f32> {
+        let mut tokens = Vec::new();
+        match serde_json::from_str::<JsonValue>(line) {
+            Ok(value) => tokenize_value(&value, &mut tokens),
+            Err(_) => tokens.extend(tokenize(line)),
+        }
+
+        let mut seen = HashSet::new();
+        let tokens: Vec<Vec<char>> = tokens
+            .into_iter()
+            .filter(|token| seen.insert(token.clone()))
+            .map(|token| token.chars().collect())
+            .collect();
+
+        let mut total = 0.0;
+        for (term, &max_distance) in self.terms.iter().zip(&self.max_distances) {
+            let best = tokens
+                .iter()
+                .filter_map(|token| Self::term_score(term, token, max_distance))
+                .fold(None, |best: Option<f32>, score| Some(best.map_or(score, |b| b.max(score))))?;
+            total += best;
+        }
+
+        Some(total / self.terms.len() as f32)
+    }
+
+    /// 计算单个查询词与词元的匹配得分
+    fn term_score(term: &[char], token: &[char], max_distance: usize) -> Option<f32> {
+        if term == token {
+            return Some(1.0);
+        }
+        if token.len() + max_distance < term.len() {
+            return None;
+        }
+
+        let distance = substring_distance(term, token, max_distance)?;
+        if distance == 0 {
+            Some(0.9)
+        } else {
+            Some(0.8 * (1.0 - distance as f32 / (term.len() + 1) as f32))
+        }
+    }
+}
+
+/// 查询词与词元任意子串之间的最小编辑距离，超过上限时返回 `None`
+///
+/// 在 Sellers 算法的基础上把相邻字符交换计为一次编辑（OSA 距离），更贴近常见的拼写错误。
+fn substring_distance(term: &[char], token: &[char], max_distance: usize) -> Option<usize> {
+    // previous[j]：term 前 i 个字符匹配到 token 第 j 个字符结尾的最小距离
+    let mut before_previous = vec![0usize; token.len() + 1];
+    let mut previous = vec![0usize; token.len() + 1];
+    let mut current = vec![0usize; token.len() + 1];
+
+    for (i, &tc) in term.iter().enumerate() {
+        current[0] = i + 1;
+        let mut row_min = current[0];
+        for (j, &kc) in token.iter().enumerate() {
+            let cost = if tc == kc { 0 } else { 1 };
+            let mut distance = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            // 相邻字符交换
+            if i > 0 && j > 0 && tc == token[j - 1] && term[i - 1] == kc {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current[j + 1] = distance;
+            row_min = row_min.min(distance);
+        }
+        // 整行都超过上限，不可能再满足
+        if row_min > max_distance {
+            return None;
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous.iter().copied().min().filter(|&d| d <= max_distance)
+}
+
+/// 并行扫描文件，返回按得分降序（同分按行号升序）排列的 `(行号, 得分)`
+pub fn fuzzy_scan(path: &str, line_offsets: &[u64], matcher: &FuzzyMatcher) -> AppResult<Vec<(usize, f32)>> {
+    let lines = MappedLines::open(path, line_offsets)?;
+    let mut results = lines.par_filter_map(|line_num, line| {
+        matcher.score_line(line).map(|score| (line_num, score))
+    });
+
+    sort_by_score(&mut results);
+    Ok(results)
+}
+
+/// 按得分降序、同分按行号升序排列 `(行号, 得分)`
+pub fn sort_by_score(results: &mut [(usize, f32)]) {
+    results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+}
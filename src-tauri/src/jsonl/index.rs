@@ -0,0 +1,462 @@
+use crate::jsonl::{query_terms, tokenize, FileFingerprint, Fnv64, QueryTerm};
+use crate::models::{AppError, AppResult, LoadingProgress};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 索引文件头，分词规则变化时需要升级版本号
+const INDEX_MAGIC: &[u8; 8] = b"SSIDX003";
+
+/// 持久化的全文倒排索引
+///
+/// 记录每个词元出现过的行号，用于缩小关键词搜索需要校验的行的范围。
+/// 词元取自行的原始文本（与关键词搜索匹配的文本相同），索引与文件指纹绑定，文件变化后即失效。
+pub struct InvertedIndex {
+    /// 源文件路径
+    source: String,
+
+    /// 构建时的文件指纹
+    fingerprint: FileFingerprint,
+
+    /// 源文件总行数
+    total_lines: usize,
+
+    /// 词元 -> 升序行号列表
+    postings: HashMap<String, Vec<u32>>,
+}
+
+impl InvertedIndex {
+    /// 扫描文件构建索引
+    pub fn build<F>(path: &str, cancelled: &AtomicBool, progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        println!("构建搜索索引: {}", path);
+
+        let fingerprint = FileFingerprint::of(path)?;
+        let file_size = fingerprint.size;
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let progress_interval = Duration::from_millis(100);
+        let mut last_progress_time = Instant::now();
+
+        let mut postings: HashMap<String, Vec<u32>> = HashMap::new();
+        let mut line_tokens = Vec::new();
+        let mut seen = HashSet::new();
+        let mut bytes_read = 0u64;
+        let mut line_num = 0usize;
+        let mut line = String::new();
+
+        progress_callback(LoadingProgress {
+            current: 0,
+            total: file_size as usize,
+            stage: "构建搜索索引".to_string(),
+            percentage: 0.0,
+        });
+
+        loop {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(AppError::General("索引构建已取消".to_string()));
+            }
+
+            line.clear();
+            let bytes = reader.read_line(&mut line)?;
+            if bytes == 0 {
+                break;
+            }
+            bytes_read += bytes as u64;
+
+            let trimmed = line.trim();
+            if !trimmed.is_empty() {
+                line_tokens.clear();
+                line_tokens.extend(tokenize(trimmed));
+
+                // 每行每个词元只记录一次
+                seen.clear();
+                for token in line_tokens.drain(..) {
+                    if seen.insert(token.clone()) {
+                        postings.entry(token).or_default().push(line_num as u32);
+                    }
+                }
+            }
+            line_num += 1;
+
+            let now = Instant::now();
+            if now.duration_since(last_progress_time) >= progress_interval {
+                last_progress_time = now;
+                progress_callback(LoadingProgress {
+                    current: bytes_read as usize,
+                    total: file_size as usize,
+                    stage: "构建搜索索引".to_string(),
+                    percentage: if file_size > 0 {
+                        (bytes_read as f32 / file_size as f32) * 100.0
+                    } else {
+                        100.0
+                    },
+                });
+            }
+        }
+
+        println!("搜索索引构建完成: 行数={}, 词元数={}", line_num, postings.len());
+
+        Ok(Self {
+            source: path.to_string(),
+            fingerprint,
+            total_lines: line_num,
+            postings,
+        })
+    }
+
+    /// 源文件对应的索引文件路径
+    pub fn index_path(index_dir: &Path, source: &str) -> PathBuf {
+        index_dir.join(format!("{:016x}.idx", Fnv64::hash(source.as_bytes())))
+    }
+
+    /// 源文件路径
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 构建时的文件指纹
+    pub fn fingerprint(&self) -> FileFingerprint {
+        self.fingerprint
+    }
+
+    /// 源文件总行数
+    pub fn total_lines(&self) -> usize {
+        self.total_lines
+    }
+
+    /// 不同词元的数量
+    pub fn token_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// 索引是否仍与源文件一致
+    pub fn is_fresh(&self) -> bool {
+        FileFingerprint::of(&self.source)
+            .map(|fingerprint| fingerprint == self.fingerprint)
+            .unwrap_or(false)
+    }
+
+    /// 获取词元的行号列表
+    pub fn lookup(&self, token: &str) -> &[u32] {
+        self.postings.get(token).map(|lines| lines.as_slice()).unwrap_or(&[])
+    }
+
+    /// 求出可能包含关键词（按子串匹配）的候选行，行号升序
+    ///
+    /// 关键词中的每个词按其在关键词中的位置，对应到与之相同、以之开头、以之结尾或含有它的词元，
+    /// 每个中日韩单字对应到含有该字的词元，
+    /// 各项候选行的交集包含了全部匹配行，候选行仍需逐行校验；
+    /// 关键词中没有可查找的词时返回 `None`，由调用方退回全文扫描。
+    pub fn candidates(&self, keyword: &str) -> Option<Vec<usize>> {
+        let mut lists: Vec<Vec<u32>> = query_terms(keyword).iter().map(|term| self.term_lines(term)).collect();
+
+        // 从最短的列表开始求交集
+        lists.sort_by_key(Vec::len);
+        let mut lists = lists.into_iter();
+        let mut candidates = lists.next()?;
+        for lines in lists {
+            if candidates.is_empty() {
+                break;
+            }
+            candidates.retain(|line| lines.binary_search(line).is_ok());
+        }
+
+        Some(candidates.into_iter().map(|line| line as usize).collect())
+    }
+
+    /// 可能含有某个查询项的行，行号升序
+    fn term_lines(&self, term: &QueryTerm) -> Vec<u32> {
+        match term {
+            QueryTerm::Word { text, starts_token: true, ends_token: true } => self.lookup(text).to_vec(),
+            // 词的一端可能与相邻字符连成更长的词元
+            QueryTerm::Word { text, starts_token, ends_token } => self.lines_with(|token| match (starts_token, ends_token) {
+                (true, _) => token.starts_with(text.as_str()),
+                (_, true) => token.ends_with(text.as_str()),
+                _ => token.contains(text.as_str()),
+            }),
+            QueryTerm::Cjk(c) => self.lines_with(|token| token.contains(*c)),
+        }
+    }
+
+    /// 含有满足条件的词元的行，行号升序
+    fn lines_with<F>(&self, matches: F) -> Vec<u32>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut lines: Vec<u32> = self
+            .postings
+            .iter()
+            .filter(|(token, _)| matches(token))
+            .flat_map(|(_, lines)| lines.iter().copied())
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// 写入磁盘，先写临时文件再替换，避免留下损坏的索引
+    pub fn save(&self, index_path: &Path) -> AppResult<()> {
+        if let Some(parent) = index_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let temp_path = index_path.with_extension("idx.tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&temp_path)?);
+            writer.write_all(INDEX_MAGIC)?;
+            write_bytes(&mut writer, self.source.as_bytes())?;
+            write_varint(&mut writer, self.fingerprint.size)?;
+            write_varint(&mut writer, self.fingerprint.modified_ms)?;
+            write_varint(&mut writer, self.fingerprint.sample_hash)?;
+            write_varint(&mut writer, self.total_lines as u64)?;
+            write_varint(&mut writer, self.postings.len() as u64)?;
+
+            for (token, lines) in &self.postings {
+                write_bytes(&mut writer, token.as_bytes())?;
+                write_varint(&mut writer, lines.len() as u64)?;
+                // 行号差分编码
+                let mut previous = 0u32;
+                for &line in lines {
+                    write_varint(&mut writer, (line - previous) as u64)?;
+                    previous = line;
+                }
+            }
+
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        std::fs::rename(&temp_path, index_path)?;
+
+        println!("搜索索引已保存: {}", index_path.display());
+        Ok(())
+    }
+
+    /// 从磁盘读取索引
+    pub fn load(index_path: &Path) -> AppResult<Self> {
+        let mut reader = BufReader::new(File::open(index_path)?);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC {
+            return Err(AppError::General("索引文件格式不正确".to_string()));
+        }
+
+        let source = String::from_utf8(read_bytes(&mut reader)?)
+            .map_err(|_| AppError::General("索引文件已损坏".to_string()))?;
+        let fingerprint = FileFingerprint {
+            size: read_varint(&mut reader)?,
+            modified_ms: read_varint(&mut reader)?,
+            sample_hash: read_varint(&mut reader)?,
+        };
+        let total_lines = read_varint(&mut reader)? as usize;
+        let token_count = read_varint(&mut reader)? as usize;
+
+        let mut postings = HashMap::with_capacity(token_count);
+        for _ in 0..token_count {
+            let token = String::from_utf8(read_bytes(&mut reader)?)
+                .map_err(|_| AppError::General("索引文件已损坏".to_string()))?;
+            let count = read_varint(&mut reader)? as usize;
+            let mut lines = Vec::with_capacity(count);
+            let mut previous = 0u32;
+            for _ in 0..count {
+                previous += read_varint(&mut reader)? as u32;
+                lines.push(previous);
+            }
+            postings.insert(token, lines);
+        }
+
+        Ok(Self {
+            source,
+            fingerprint,
+            total_lines,
+            postings,
+        })
+    }
+}
+
+/// 写入变长整数
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> AppResult<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            writer.write_all(&[byte])?;
+            return Ok(());
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+/// 读取变长整数
+fn read_varint<R: Read>(reader: &mut R) -> AppResult<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift >= 64 {
+            return Err(AppError::General("索引文件已损坏".to_string()));
+        }
+    }
+}
+
+/// 写入带长度前缀的字节串
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> AppResult<()> {
+    write_varint(writer, bytes.len() as u64)?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+/// 读取带长度前缀的字节串
+fn read_bytes<R: Read>(reader: &mut R) -> AppResult<Vec<u8>> {
+    let len = read_varint(reader)? as usize;
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonl::line_matches;
+
+    const ROWS: [&str; 6] = [
+        r#"{"msg":"error: disk full","code":500}"#,
+        r#"{"msg":"errors were ignored","code":200}"#,
+        r#"{"msg":"no problem","code":404}"#,
+        r#"{"msg":"line\nbreak","tags":["terror","x_1"]}"#,
+        r#"{"msg":"Disk Error","code":5000}"#,
+        "",
+    ];
+
+    /// 写入测试文件并建立索引
+    fn build(name: &str) -> (PathBuf, InvertedIndex) {
+        build_rows(name, &ROWS)
+    }
+
+    fn build_rows(name: &str, rows: &[&str]) -> (PathBuf, InvertedIndex) {
+        let dir = std::env::temp_dir().join(format!("smart-slice-index-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.jsonl");
+        std::fs::write(&path, rows.join("\n")).unwrap();
+        let index = InvertedIndex::build(path.to_str().unwrap(), &AtomicBool::new(false), |_| {}).unwrap();
+        (dir, index)
+    }
+
+    /// 逐行扫描得到的匹配行
+    fn scan(keyword: &str) -> Vec<usize> {
+        let keyword = keyword.to_lowercase();
+        ROWS.iter()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty() && line_matches(line, &keyword))
+            .map(|(line_num, _)| line_num)
+            .collect()
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        let values = [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX];
+        let mut bytes = Vec::new();
+        for &value in &values {
+            write_varint(&mut bytes, value).unwrap();
+        }
+        assert_eq!(bytes.len(), 1 + 1 + 1 + 2 + 2 + 5 + 10);
+
+        let mut reader = bytes.as_slice();
+        for &value in &values {
+            assert_eq!(read_varint(&mut reader).unwrap(), value);
+        }
+        assert!(read_varint(&mut [0xffu8; 11].as_slice()).is_err());
+    }
+
+    #[test]
+    fn single_word_uses_index() {
+        let (dir, index) = build("single");
+        // 单个词可能是更长词元的一部分
+        assert_eq!(index.candidates("error"), Some(vec![0, 1, 3, 4]));
+        assert_eq!(index.candidates("500"), Some(vec![0, 4]));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn phrase_intersects_postings() {
+        let (dir, index) = build("phrase");
+        // 首词以之结尾、尾词以之开头，中间的词必须完整出现
+        assert_eq!(index.candidates("error: disk"), Some(vec![0, 4]));
+        assert_eq!(index.candidates("rror: disk fu"), Some(vec![0]));
+        assert_eq!(index.candidates("were ignored"), Some(vec![1]));
+        assert_eq!(index.candidates("\",\""), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn candidates_cover_every_match() {
+        let (dir, index) = build("cover");
+        let keywords = ["error", "Error", "rors", "disk full", ": disk", "\\nbreak", "nbreak", "x_1", "\"code\":5", "err", "r: d"];
+        for keyword in keywords {
+            let candidates = index.candidates(keyword).unwrap();
+            for line_num in scan(keyword) {
+                assert!(candidates.contains(&line_num), "{keyword:?} 缺少第 {line_num} 行");
+            }
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cjk_query_uses_index() {
+        let rows = [
+            r#"{"text":"全心全意为人民服务"}"#,
+            r#"{"text":"人民币汇率"}"#,
+            r#"{"text":"服务器维护"}"#,
+            r#"{"text":"hello 世界"}"#,
+        ];
+        let (dir, index) = build_rows("cjk", &rows);
+        assert_eq!(index.candidates("人民"), Some(vec![0, 1]));
+        assert_eq!(index.candidates("人民服务"), Some(vec![0]));
+        // 跨越分词边界的子串同样能找到
+        assert_eq!(index.candidates("民服"), Some(vec![0]));
+        assert_eq!(index.candidates("hello世界"), Some(vec![3]));
+        assert_eq!(index.candidates("汇率 维护"), Some(vec![]));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let (dir, index) = build("save");
+        let index_path = InvertedIndex::index_path(&dir, index.source());
+        index.save(&index_path).unwrap();
+
+        let loaded = InvertedIndex::load(&index_path).unwrap();
+        assert_eq!(loaded.source(), index.source());
+        assert_eq!(loaded.fingerprint(), index.fingerprint());
+        // 最后的空字符串只是文件末尾的换行
+        assert_eq!(loaded.total_lines(), ROWS.len() - 1);
+        assert_eq!(loaded.token_count(), index.token_count());
+        assert_eq!(loaded.postings, index.postings);
+
+        std::fs::write(&index_path, b"SSIDX000").unwrap();
+        assert!(InvertedIndex::load(&index_path).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn modified_file_invalidates_index() {
+        let (dir, index) = build("fresh");
+        assert!(index.is_fresh());
+
+        std::fs::write(index.source(), "{\"msg\":\"changed\"}\n").unwrap();
+        assert!(!index.is_fresh());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+use crate::models::AppResult;
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::fs::File;
+use std::ops::Range;
+
+/// 以内存映射方式打开的 JSONL 文件，可按行号并行访问
+pub struct MappedLines<'a> {
+    /// 文件内容映射，空文件为 `None`
+    mmap: Option<Mmap>,
+
+    /// 每行的起始字节偏移
+    line_offsets: &'a [u64],
+}
+
+impl<'a> MappedLines<'a> {
+    /// 映射文件
+    pub fn open(path: &str, line_offsets: &'a [u64]) -> AppResult<Self> {
+        Self::from_file(&File::open(path)?, line_offsets)
+    }
+
+    /// 映射已打开的文件
+    pub fn from_file(file: &File, line_offsets: &'a [u64]) -> AppResult<Self> {
+        let mmap = if file.metadata()?.len() == 0 {
+            None
+        } else {
+            // 安全性：文件以只读方式映射，扫描期间假定文件不被截断
+            Some(unsafe { Mmap::map(file)? })
+        };
+
+        Ok(Self { mmap, line_offsets })
+    }
+
+    /// 行数
+    pub fn len(&self) -> usize {
+        self.line_offsets.len()
+    }
+
+    /// 是否没有任何行
+    pub fn is_empty(&self) -> bool {
+        self.line_offsets.is_empty()
+    }
+
+    /// 获取指定行的原始字节，包括行尾的换行符
+    pub fn raw_line(&self, line_num: usize) -> Option<&[u8]> {
+        let mmap = self.mmap.as_ref()?;
+        let start = *self.line_offsets.get(line_num)? as usize;
+        let end = self
+            .line_offsets
+            .get(line_num + 1)
+            .map(|&offset| offset as usize)
+            .unwrap_or(mmap.len())
+            .min(mmap.len());
+        mmap.get(start..end)
+    }
+
+    /// 获取指定行（已去除首尾空白），非 UTF-8 的行返回 `None`
+    pub fn line(&self, line_num: usize) -> Option<&str> {
+        std::str::from_utf8(self.raw_line(line_num)?).ok().map(str::trim)
+    }
+
+    /// 并行处理所有非空行，结果按行号顺序返回
+    pub fn par_filter_map<T, F>(&self, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &str) -> Option<T> + Sync + Send,
+    {
+        self.par_filter_map_range(0..self.len(), f)
+    }
+
+    /// 并行处理指定行号区间内的非空行，便于分批报告进度
+    pub fn par_filter_map_range<T, F>(&self, range: Range<usize>, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &str) -> Option<T> + Sync + Send,
+    {
+        range
+            .into_par_iter()
+            .filter_map(|line_num| {
+                let line = self.line(line_num)?;
+                if line.is_empty() {
+                    return None;
+                }
+                f(line_num, line)
+            })
+            .collect()
+    }
+
+    /// 并行处理指定行号的非空行，结果按给定顺序返回
+    pub fn par_filter_map_lines<T, F>(&self, lines: &[usize], f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, &str) -> Option<T> + Sync + Send,
+    {
+        lines
+            .par_iter()
+            .filter_map(|&line_num| {
+                let line = self.line(line_num)?;
+                if line.is_empty() {
+                    return None;
+                }
+                f(line_num, line)
+            })
+            .collect()
+    }
+}
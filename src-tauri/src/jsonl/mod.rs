@@ -1,5 +1,51 @@
 pub mod reader;
 pub mod debug;
+pub mod transform;
+pub mod search;
+pub mod path;
+pub mod tokenizer;
+pub mod fingerprint;
+pub mod index;
+pub mod lines;
+pub mod fuzzy;
+pub mod sort;
+pub mod dedup;
+pub mod near_dup;
+pub mod schema;
+pub mod sketch;
+pub mod stats;
+pub mod chart;
+pub mod validation;
+pub mod quality;
+pub mod flatten;
+pub mod diff;
+pub mod text_diff;
+pub mod edit;
+pub mod save;
+pub mod draft;
 
 pub use reader::*;
-pub use debug::*; 
\ No newline at end of file
+pub use debug::*;
+pub use transform::*;
+pub use search::*;
+pub use path::*;
+pub use tokenizer::*;
+pub use fingerprint::*;
+pub use index::*;
+pub use lines::*;
+pub use fuzzy::*;
+pub use sort::*;
+pub use dedup::*;
+pub use near_dup::*;
+pub use schema::*;
+pub use sketch::*;
+pub use stats::*;
+pub use chart::*;
+pub use validation::*;
+pub use quality::*;
+pub use flatten::*;
+pub use diff::*;
+pub use text_diff::*;
+pub use edit::*;
+pub use save::*;
+pub use draft::*;
\ No newline at end of file
@@ -0,0 +1,380 @@
+use crate::jsonl::{tokenize_value, Fnv64, JsonPath, MappedLines};
+use crate::models::{AppError, AppResult, JsonValue, LoadingProgress};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// MinHash 签名长度
+const NUM_HASHES: usize = 64;
+
+/// 组成一个 shingle 的连续词元数
+const SHINGLE_SIZE: usize = 3;
+
+/// 每批并行处理的行数，每批结束后报告一次进度
+const BATCH_LINES: usize = 50_000;
+
+/// 同一分桶内每行最多与之前的多少行比较，避免大桶退化为平方复杂度
+const MAX_BUCKET_COMPARISONS: usize = 32;
+
+/// splitmix64 混合函数，用于从一个哈希派生出多个独立哈希
+fn mix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// 第 `i` 个哈希函数的种子
+fn seed(i: usize) -> u64 {
+    mix64(i as u64 ^ 0x5851f42d4c957f2d)
+}
+
+/// 一行文本的 MinHash 签名
+type Signature = [u32; NUM_HASHES];
+
+/// 近似重复分析参数与签名计算
+pub struct MinHasher {
+    /// 参与比较的字段
+    field: JsonPath,
+
+    /// Jaccard 相似度阈值 (0-1]
+    threshold: f32,
+
+    /// 每个分桶带包含的签名行数
+    rows_per_band: usize,
+}
+
+impl MinHasher {
+    /// 创建签名计算器，按阈值选择 LSH 分带方式
+    pub fn new(field: &str, threshold: f32) -> AppResult<Self> {
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            return Err(AppError::General(format!("相似度阈值必须在 (0, 1] 之间: {}", threshold)));
+        }
+        let field = JsonPath::parse(field)?;
+
+        // LSH 的候选阈值约为 (1/b)^(1/r)，取不超过目标阈值的最大行数以保证召回
+        let mut rows_per_band = 1;
+        for rows in [2, 4, 8, 16, 32] {
+            let bands = (NUM_HASHES / rows) as f32;
+            if (1.0 / bands).powf(1.0 / rows as f32) <= threshold {
+                rows_per_band = rows;
+            }
+        }
+
+        Ok(Self {
+            field,
+            threshold,
+            rows_per_band,
+        })
+    }
+
+    /// 分带数量
+    fn bands(&self) -> usize {
+        NUM_HASHES / self.rows_per_band
+    }
+
+    /// 计算一行的签名，字段缺失或没有任何词元时返回 `None`
+    fn signature(&self, line: &str) -> Option<Signature> {
+        let row = serde_json::from_str::<JsonValue>(line).ok()?;
+        let value = self.field.get(&row)?;
+
+        let mut tokens = Vec::new();
+        tokenize_value(value, &mut tokens);
+        if tokens.is_empty() {
+            return None;
+        }
+
+        let mut signature = [u32::MAX; NUM_HASHES];
+        for window in tokens.windows(SHINGLE_SIZE.min(tokens.len())) {
+            let mut hasher = Fnv64::new();
+            for token in window {
+                hasher.write(token.as_bytes());
+                hasher.write(&[0x1f]);
+            }
+            let shingle = hasher.finish();
+            for (i, slot) in signature.iter_mut().enumerate() {
+                let h = mix64(shingle ^ seed(i)) as u32;
+                if h < *slot {
+                    *slot = h;
+                }
+            }
+        }
+        Some(signature)
+    }
+
+    /// 将签名压缩为每个分带一个哈希
+    fn band_hashes(&self, signature: &Signature) -> Vec<u32> {
+        signature
+            .chunks(self.rows_per_band)
+            .map(|band| {
+                let mut hasher = Fnv64::new();
+                for value in band {
+                    hasher.write(&value.to_le_bytes());
+                }
+                hasher.finish() as u32
+            })
+            .collect()
+    }
+
+    /// 两个签名的估计 Jaccard 相似度是否达到阈值
+    fn similar(&self, a: &Signature, b: &Signature) -> bool {
+        let equal = a.iter().zip(b.iter()).filter(|(x, y)| x == y).count();
+        equal as f32 / NUM_HASHES as f32 >= self.threshold
+    }
+}
+
+/// 并查集
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// 合并两个集合，已在同一集合时返回 `false`
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        // 以较小的下标为根，使簇的代表行是最早出现的行
+        let (root, child) = if a < b { (a, b) } else { (b, a) };
+        self.parent[child] = root;
+        true
+    }
+}
+
+/// 近似重复分析结果
+pub struct NearDupAnalysis {
+    /// 文件路径
+    pub path: String,
+
+    /// 参与比较的字段路径
+    pub field: String,
+
+    /// 相似度阈值
+    pub threshold: f32,
+
+    /// 参与比较的行数
+    pub compared_lines: usize,
+
+    /// 近似重复簇，每簇为升序行号，首行为代表行；按簇大小降序、首行升序排列
+    pub clusters: Vec<Vec<usize>>,
+}
+
+impl NearDupAnalysis {
+    /// 使用 MinHash LSH 并行查找指定字段近似重复的行
+    pub fn analyze<F>(path: &str, line_offsets: &[u64], field: &str, threshold: f32, progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let hasher = MinHasher::new(field, threshold)?;
+        let lines = MappedLines::open(path, line_offsets)?;
+        let total = lines.len();
+        let bands = hasher.bands();
+
+        // 第一遍：计算每行的分带哈希，只保留分带哈希以控制内存
+        let mut compared: Vec<u32> = Vec::new();
+        let mut band_table: Vec<u32> = Vec::new();
+        let mut start = 0;
+        while start < total {
+            let end = (start + BATCH_LINES).min(total);
+            let batch = lines.par_filter_map_range(start..end, |line_num, line| {
+                hasher.signature(line).map(|signature| (line_num as u32, hasher.band_hashes(&signature)))
+            });
+            for (line_num, band_hashes) in batch {
+                compared.push(line_num);
+                band_table.extend(band_hashes);
+            }
+            start = end;
+
+            progress_callback(LoadingProgress {
+                current: start,
+                total,
+                stage: "计算文本签名".to_string(),
+                percentage: start as f32 / total as f32 * 90.0,
+            });
+        }
+
+        // 按分带哈希分桶，同桶的行为候选对
+        let mut buckets: Vec<Vec<usize>> = Vec::new();
+        for band in 0..bands {
+            let mut keyed: Vec<(u32, usize)> = (0..compared.len())
+                .map(|i| (band_table[i * bands + band], i))
+                .collect();
+            keyed.par_sort_unstable();
+
+            let mut first = 0;
+            while first < keyed.len() {
+                let mut last = first + 1;
+                while last < keyed.len() && keyed[last].0 == keyed[first].0 {
+                    last += 1;
+                }
+                if last - first > 1 {
+                    buckets.push(keyed[first..last].iter().map(|(_, i)| *i).collect());
+                }
+                first = last;
+            }
+        }
+        drop(band_table);
+
+        progress_callback(LoadingProgress {
+            current: total,
+            total,
+            stage: format!("校验 {} 个候选分桶", buckets.len()),
+            percentage: 92.0,
+        });
+
+        // 第二遍：只为候选行重新计算完整签名，用于排除分带哈希的误报
+        let mut candidates: Vec<usize> = buckets.iter().flatten().copied().collect();
+        candidates.par_sort_unstable();
+        candidates.dedup();
+        let signatures: HashMap<usize, Signature> = candidates
+            .par_iter()
+            .filter_map(|&i| {
+                let line = lines.line(compared[i] as usize)?;
+                hasher.signature(line).map(|signature| (i, signature))
+            })
+            .collect();
+
+        let mut sets = DisjointSet::new(compared.len());
+        for bucket in &buckets {
+            for (pos, &i) in bucket.iter().enumerate().skip(1) {
+                let Some(sig_i) = signatures.get(&i) else { continue };
+                for &j in bucket[..pos].iter().rev().take(MAX_BUCKET_COMPARISONS) {
+                    if sets.find(i) == sets.find(j) {
+                        break;
+                    }
+                    if let Some(sig_j) = signatures.get(&j) {
+                        if hasher.similar(sig_i, sig_j) {
+                            sets.union(i, j);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &i in &candidates {
+            by_root.entry(sets.find(i)).or_default().push(compared[i] as usize);
+        }
+        let mut clusters: Vec<Vec<usize>> = by_root
+            .into_values()
+            .filter(|cluster| cluster.len() > 1)
+            .map(|mut cluster| {
+                cluster.sort_unstable();
+                cluster
+            })
+            .collect();
+        clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+
+        progress_callback(LoadingProgress {
+            current: total,
+            total,
+            stage: "近似重复检测完成".to_string(),
+            percentage: 100.0,
+        });
+
+        Ok(Self {
+            path: path.to_string(),
+            field: field.to_string(),
+            threshold,
+            compared_lines: compared.len(),
+            clusters,
+        })
+    }
+
+    /// 属于某个近似重复簇的行数
+    pub fn clustered_rows(&self) -> usize {
+        self.clusters.iter().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 由若干词组成的文本，`last` 替换最后一个词
+    fn text(prefix: &str, last: &str) -> String {
+        let mut words: Vec<String> = (0..30).map(|i| format!("{}{}", prefix, i)).collect();
+        words.push(last.to_string());
+        words.join(" ")
+    }
+
+    fn row(text: &str) -> String {
+        json!({ "text": text }).to_string()
+    }
+
+    /// 写入测试文件并分析近似重复
+    fn analyze(name: &str, rows: &[String], threshold: f32) -> NearDupAnalysis {
+        let path = std::env::temp_dir().join(format!("smart-slice-near-dup-{}-{}.jsonl", std::process::id(), name));
+        let mut offsets = Vec::new();
+        let mut content = String::new();
+        for row in rows {
+            offsets.push(content.len() as u64);
+            content.push_str(row);
+            content.push('\n');
+        }
+        std::fs::write(&path, content).unwrap();
+        let analysis = NearDupAnalysis::analyze(path.to_str().unwrap(), &offsets, "text", threshold, |_| {}).unwrap();
+        std::fs::remove_file(path).unwrap();
+        analysis
+    }
+
+    #[test]
+    fn signatures_estimate_similarity() {
+        let hasher = MinHasher::new("text", 0.8).unwrap();
+        let a = hasher.signature(&row(&text("word", "end"))).unwrap();
+        let same = hasher.signature(&row(&text("word", "end"))).unwrap();
+        let near = hasher.signature(&row(&text("word", "finish"))).unwrap();
+        let other = hasher.signature(&row(&text("term", "end"))).unwrap();
+
+        assert_eq!(a, same);
+        assert!(hasher.similar(&a, &near));
+        assert!(!hasher.similar(&a, &other));
+        assert!(hasher.signature(r#"{"other":"x"}"#).is_none());
+        assert!(MinHasher::new("text", 0.0).is_err());
+    }
+
+    #[test]
+    fn disjoint_set_roots_at_earliest_row() {
+        let mut sets = DisjointSet::new(5);
+        assert!(sets.union(3, 1));
+        assert!(sets.union(4, 3));
+        assert!(!sets.union(1, 4));
+        assert_eq!(sets.find(4), 1);
+        assert_eq!(sets.find(0), 0);
+        assert_ne!(sets.find(2), sets.find(1));
+    }
+
+    #[test]
+    fn clusters_near_duplicate_rows() {
+        let rows = vec![
+            row(&text("word", "end")),
+            row(&text("term", "end")),
+            row(&text("word", "finish")),
+            json!({ "id": 3 }).to_string(),
+            row(&text("word", "stop")),
+            row(&text("term", "stop")),
+            row(&text("other", "end")),
+        ];
+        let analysis = analyze("clusters", &rows, 0.8);
+
+        assert_eq!(analysis.compared_lines, 6);
+        assert_eq!(analysis.clusters, vec![vec![0, 2, 4], vec![1, 5]]);
+        assert_eq!(analysis.clustered_rows(), 5);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::models::{AppError, AppResult, JsonValue};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// JSON 路径中的一段
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PathSegment {
+    /// 对象字段
+    Key(String),
+
+    /// 数组下标
+    Index(usize),
+}
+
+/// 点号形式的 JSON 路径，例如 `address.city`、`tags[0]`、`messages[1].content`
+///
+/// 含有 `.`、`[`、`]` 或 `"` 的字段名写作 `["a.b"]`。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct JsonPath(Vec<PathSegment>);
+
+impl JsonPath {
+    /// 空路径（指向整行）
+    pub fn root() -> Self {
+        Self(Vec::new())
+    }
+
+    /// 解析路径字符串，允许可选的 `$` 或 `.` 前缀
+    pub fn parse(input: &str) -> AppResult<Self> {
+        let invalid = |reason: &str| AppError::General(format!("无效的字段路径 '{}': {}", input, reason));
+
+        let mut rest = input.trim();
+        rest = rest.strip_prefix('$').unwrap_or(rest);
+
+        let mut segments = Vec::new();
+        let mut chars = rest.chars().peekable();
+        let mut expect_key = true;
+
+        while let Some(&c) = chars.peek() {
+            match c {
+                '.' => {
+                    chars.next();
+                    expect_key = true;
+                }
+                '[' => {
+                    chars.next();
+                    if chars.peek() == Some(&'"') {
+                        // 带引号的字段名
+                        chars.next();
+                        let mut key = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('\\') => match chars.next() {
+                                    Some(escaped) => key.push(escaped),
+                                    None => return Err(invalid("转义字符不完整")),
+                                },
+                                Some('"') => break,
+                                Some(ch) => key.push(ch),
+                                None => return Err(invalid("缺少结束引号")),
+                            }
+                        }
+                        if chars.next() != Some(']') {
+                            return Err(invalid("缺少 ']'"));
+                        }
+                        segments.push(PathSegment::Key(key));
+                    } else {
+                        let mut digits = String::new();
+                        loop {
+                            match chars.next() {
+                                Some(']') => break,
+                                Some(ch) => digits.push(ch),
+                                None => return Err(invalid("缺少 ']'")),
+                            }
+                        }
+                        let index = digits.trim().parse::<usize>().map_err(|_| invalid("数组下标必须是非负整数"))?;
+                        segments.push(PathSegment::Index(index));
+                    }
+                    expect_key = false;
+                }
+                _ => {
+                    if !expect_key {
+                        return Err(invalid("字段名前缺少 '.'"));
+                    }
+                    let mut key = String::new();
+                    while let Some(&ch) = chars.peek() {
+                        if ch == '.' || ch == '[' {
+                            break;
+                        }
+                        key.push(ch);
+                        chars.next();
+                    }
+                    segments.push(PathSegment::Key(key));
+                    expect_key = false;
+                }
+            }
+        }
+
+        Ok(Self(segments))
+    }
+
+    /// 路径的各段
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// 是否为空路径
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 追加一个字段名，返回新路径
+    pub fn child_key(&self, key: &str) -> Self {
+        let mut segments = self.0.clone();
+        segments.push(PathSegment::Key(key.to_string()));
+        Self(segments)
+    }
+
+    /// 追加一个数组下标，返回新路径
+    pub fn child_index(&self, index: usize) -> Self {
+        let mut segments = self.0.clone();
+        segments.push(PathSegment::Index(index));
+        Self(segments)
+    }
+
+    /// 读取路径指向的值
+    pub fn get<'a>(&self, value: &'a JsonValue) -> Option<&'a JsonValue> {
+        let mut current = value;
+        for segment in &self.0 {
+            current = match (segment, current) {
+                (PathSegment::Key(key), JsonValue::Object(map)) => map.get(key)?,
+                (PathSegment::Index(index), JsonValue::Array(items)) => items.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// 删除路径指向的值并返回，路径不存在时返回 `None`
+    pub fn remove(&self, target: &mut JsonValue) -> Option<JsonValue> {
+        let (last, parents) = self.0.split_last()?;
+        let mut current = target;
+        for segment in parents {
+            current = match (segment, current) {
+                (PathSegment::Key(key), JsonValue::Object(map)) => map.get_mut(key)?,
+                (PathSegment::Index(index), JsonValue::Array(items)) => items.get_mut(*index)?,
+                _ => return None,
+            };
+        }
+        match (last, current) {
+            (PathSegment::Key(key), JsonValue::Object(map)) => map.remove(key),
+            (PathSegment::Index(index), JsonValue::Array(items)) if *index < items.len() => Some(items.remove(*index)),
+            _ => None,
+        }
+    }
+
+    /// 设置路径指向的值
+    ///
+    /// 缺失的中间字段创建为空对象；数组下标必须已存在，或等于数组长度（追加到末尾）。
+    pub fn set(&self, target: &mut JsonValue, value: JsonValue) -> AppResult<()> {
+        let invalid = |reason: &str| AppError::General(format!("无法设置字段 '{}': {}", self, reason));
+
+        let Some((last, parents)) = self.0.split_last() else {
+            return Err(invalid("路径为空"));
+        };
+        let mut current = target;
+        for segment in parents {
+            current = match (segment, current) {
+                (PathSegment::Key(key), JsonValue::Object(map)) => map
+                    .entry(key.clone())
+                    .or_insert_with(|| JsonValue::Object(Default::default())),
+                (PathSegment::Index(index), JsonValue::Array(items)) => {
+                    items.get_mut(*index).ok_or_else(|| invalid("数组下标超出范围"))?
+                }
+                (PathSegment::Key(_), _) => return Err(invalid("上级值不是对象")),
+                (PathSegment::Index(_), _) => return Err(invalid("上级值不是数组")),
+            };
+        }
+
+        match (last, current) {
+            (PathSegment::Key(key), JsonValue::Object(map)) => {
+                map.insert(key.clone(), value);
+            }
+            (PathSegment::Index(index), JsonValue::Array(items)) => match (*index).cmp(&items.len()) {
+                std::cmp::Ordering::Less => items[*index] = value,
+                std::cmp::Ordering::Equal => items.push(value),
+                std::cmp::Ordering::Greater => return Err(invalid("数组下标超出范围")),
+            },
+            (PathSegment::Key(_), _) => return Err(invalid("上级值不是对象")),
+            (PathSegment::Index(_), _) => return Err(invalid("上级值不是数组")),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) => {
+                    if key.is_empty() || key.contains(['.', '[', ']', '"']) {
+                        write!(f, "[\"{}\"]", key.replace('\\', "\\\\").replace('"', "\\\""))?;
+                    } else {
+                        if i > 0 {
+                            write!(f, ".")?;
+                        }
+                        write!(f, "{}", key)?;
+                    }
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,521 @@
+use crate::jsonl::schema::{push_key, walk_paths};
+use crate::jsonl::{type_name, MappedLines, NumericSketch};
+use crate::models::{AppResult, JsonValue, LoadingProgress, QualityIssue, QualityReport};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// 每批并行处理的行数，每批结束后报告一次进度
+const BATCH_LINES: usize = 50_000;
+
+/// 每个问题最多保存的行号，用于逐行查看
+pub const MAX_ISSUE_LINES: usize = 10_000;
+
+/// 摘要中每个问题附带的示例行号数量
+const SAMPLE_LINES: usize = 20;
+
+/// 出现比例不低于该值的顶层字段视为"大多数行都有"
+const COMMON_FIELD_RATIO: f32 = 0.9;
+
+/// 长度超过中位数的该倍数（且不少于 `MIN_LONG_CHARS`）视为过长
+const LONG_FACTOR: f64 = 10.0;
+
+/// 过长判定的最小字符数
+const MIN_LONG_CHARS: f64 = 1000.0;
+
+/// 中位长度不低于该值的文本字段才检查过短
+const MIN_TEXT_MEDIAN: f64 = 50.0;
+
+/// 参与重复检测的字符串最小字符数，避免把类别值当作重复内容
+const MIN_REPEAT_CHARS: usize = 20;
+
+/// 同一值出现不少于该次数视为高频重复
+const REPEAT_MIN_COUNT: u32 = 3;
+
+/// 问题类型，按报告中的顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IssueKind {
+    /// 无法解析或不是对象的行
+    Unparseable,
+
+    /// 缺少大多数行都有的字段
+    MissingField,
+
+    /// 空字符串或只含空白
+    EmptyString,
+
+    /// 与该字段主要类型不一致
+    TypeMismatch,
+
+    /// 明显长于同字段的其他值
+    TooLong,
+
+    /// 明显短于同字段的其他值
+    TooShort,
+
+    /// 长文本值在多行中重复出现
+    RepeatedValue,
+}
+
+impl IssueKind {
+    /// 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unparseable => "unparseable",
+            Self::MissingField => "missing_field",
+            Self::EmptyString => "empty_string",
+            Self::TypeMismatch => "type_mismatch",
+            Self::TooLong => "too_long",
+            Self::TooShort => "too_short",
+            Self::RepeatedValue => "repeated_value",
+        }
+    }
+}
+
+/// 计算字符串的哈希
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 统一整数与小数，用于判断类型是否一致
+fn type_group(value: &JsonValue) -> &'static str {
+    match type_name(value) {
+        "integer" => "number",
+        other => other,
+    }
+}
+
+/// 第一遍扫描中单个路径的概况
+#[derive(Default)]
+struct PathProfile {
+    /// 含有该路径的行数
+    present_rows: usize,
+
+    /// 最近一次计入 `present_rows` 的行号
+    last_line: Option<usize>,
+
+    /// 各类型（整数与小数合并）出现次数
+    types: HashMap<&'static str, usize>,
+
+    /// 字符串长度
+    lengths: NumericSketch,
+
+    /// 顶层长文本值的哈希计数
+    repeats: HashMap<u64, u32>,
+
+    /// 顶层字段的原始字段名
+    top_level_key: Option<String>,
+}
+
+impl PathProfile {
+    fn merge(&mut self, other: PathProfile) {
+        self.present_rows += other.present_rows;
+        for (name, count) in other.types {
+            *self.types.entry(name).or_insert(0) += count;
+        }
+        self.lengths.merge(&other.lengths);
+        for (hash, count) in other.repeats {
+            *self.repeats.entry(hash).or_insert(0) += count;
+        }
+        if self.top_level_key.is_none() {
+            self.top_level_key = other.top_level_key;
+        }
+    }
+}
+
+/// 第一遍扫描的结果
+#[derive(Default)]
+struct Profile {
+    rows: usize,
+    paths: HashMap<String, PathProfile>,
+}
+
+impl Profile {
+    fn add_line(&mut self, line_num: usize, line: &str) {
+        let Ok(JsonValue::Object(map)) = serde_json::from_str::<JsonValue>(line) else {
+            return;
+        };
+        self.rows += 1;
+        let mut path = String::new();
+        for (key, value) in &map {
+            push_key(&mut path, key);
+            let profile = self.profile(&path);
+            if profile.top_level_key.is_none() {
+                profile.top_level_key = Some(key.clone());
+            }
+            if let JsonValue::String(text) = value {
+                if text.chars().count() >= MIN_REPEAT_CHARS {
+                    *profile.repeats.entry(hash_text(text)).or_insert(0) += 1;
+                }
+            }
+            walk_paths(value, &mut path, &mut |path, value| self.record(path, value, line_num));
+            path.clear();
+        }
+    }
+
+    fn profile(&mut self, path: &str) -> &mut PathProfile {
+        if !self.paths.contains_key(path) {
+            self.paths.insert(path.to_string(), PathProfile::default());
+        }
+        self.paths.get_mut(path).unwrap()
+    }
+
+    fn record(&mut self, path: &str, value: &JsonValue, line_num: usize) {
+        let profile = self.profile(path);
+        if profile.last_line != Some(line_num) {
+            profile.last_line = Some(line_num);
+            profile.present_rows += 1;
+        }
+        *profile.types.entry(type_group(value)).or_insert(0) += 1;
+        if let JsonValue::String(text) = value {
+            profile.lengths.insert(text.chars().count() as f64);
+        }
+    }
+
+    fn merge(mut self, other: Profile) -> Self {
+        self.rows += other.rows;
+        for (path, profile) in other.paths {
+            match self.paths.get_mut(&path) {
+                Some(existing) => existing.merge(profile),
+                None => {
+                    self.paths.insert(path, profile);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// 由第一遍扫描得出的单个路径的检查规则
+#[derive(Default)]
+struct PathRules {
+    /// 主要类型，字段存在多种类型时才设置
+    dominant_type: Option<&'static str>,
+
+    /// 超过该长度视为过长
+    long_over: Option<usize>,
+
+    /// 低于该长度视为过短
+    short_under: Option<usize>,
+
+    /// 需要标记的重复值哈希
+    repeated: Option<HashMap<u64, u32>>,
+}
+
+/// 单个问题的累计结果
+#[derive(Default)]
+struct IssueAccumulator {
+    count: usize,
+    last_line: Option<usize>,
+    lines: Vec<usize>,
+}
+
+impl IssueAccumulator {
+    fn record(&mut self, line_num: usize) {
+        // 同一行内（例如数组中的多个元素）只计一次
+        if self.last_line == Some(line_num) {
+            return;
+        }
+        self.last_line = Some(line_num);
+        self.count += 1;
+        if self.lines.len() < MAX_ISSUE_LINES {
+            self.lines.push(line_num);
+        }
+    }
+
+    fn merge(&mut self, other: IssueAccumulator) {
+        self.count += other.count;
+        self.lines.extend(other.lines);
+        if self.lines.len() > MAX_ISSUE_LINES {
+            self.lines.sort_unstable();
+            self.lines.truncate(MAX_ISSUE_LINES);
+        }
+    }
+}
+
+/// 第二遍扫描的结果
+#[derive(Default)]
+struct Findings {
+    issues: HashMap<(IssueKind, String), IssueAccumulator>,
+}
+
+impl Findings {
+    fn record(&mut self, kind: IssueKind, path: &str, line_num: usize) {
+        match self.issues.get_mut(&(kind, path.to_string())) {
+            Some(issue) => issue.record(line_num),
+            None => {
+                let mut issue = IssueAccumulator::default();
+                issue.record(line_num);
+                self.issues.insert((kind, path.to_string()), issue);
+            }
+        }
+    }
+
+    fn merge(mut self, other: Findings) -> Self {
+        for (key, issue) in other.issues {
+            match self.issues.get_mut(&key) {
+                Some(existing) => existing.merge(issue),
+                None => {
+                    self.issues.insert(key, issue);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// 大多数行都有的顶层字段
+struct CommonField {
+    /// 字段路径
+    path: String,
+
+    /// 原始字段名
+    key: String,
+
+    /// 含有该字段的行数
+    present_rows: usize,
+}
+
+/// 检查规则
+struct Rules {
+    /// 有效行数
+    rows: usize,
+
+    /// 大多数行都有、但并非所有行都有的顶层字段
+    common_fields: Vec<CommonField>,
+
+    /// 各路径的规则
+    paths: HashMap<String, PathRules>,
+}
+
+impl Rules {
+    /// 由第一遍扫描的结果得出检查规则
+    fn from_profile(profile: Profile) -> Self {
+        let mut common_fields = Vec::new();
+        let mut paths = HashMap::new();
+
+        for (path, stats) in profile.paths {
+            if let Some(key) = &stats.top_level_key {
+                let ratio = stats.present_rows as f32 / profile.rows.max(1) as f32;
+                if ratio >= COMMON_FIELD_RATIO && stats.present_rows < profile.rows {
+                    common_fields.push(CommonField {
+                        path: path.clone(),
+                        key: key.clone(),
+                        present_rows: stats.present_rows,
+                    });
+                }
+            }
+
+            let mut rules = PathRules::default();
+
+            // 除 null 外出现多种类型时，以出现最多的类型为准
+            let non_null: Vec<(&'static str, usize)> = stats
+                .types
+                .iter()
+                .filter(|(name, _)| **name != "null")
+                .map(|(name, count)| (*name, *count))
+                .collect();
+            if non_null.len() > 1 {
+                rules.dominant_type = non_null
+                    .iter()
+                    .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+                    .map(|(name, _)| *name);
+            }
+
+            if let Some(median) = stats.lengths.quantile(0.5) {
+                rules.long_over = Some((median * LONG_FACTOR).max(MIN_LONG_CHARS) as usize);
+                if median >= MIN_TEXT_MEDIAN {
+                    rules.short_under = Some((median / LONG_FACTOR) as usize);
+                }
+            }
+
+            let repeated: HashMap<u64, u32> = stats
+                .repeats
+                .into_iter()
+                .filter(|(_, count)| *count >= REPEAT_MIN_COUNT)
+                .collect();
+            if !repeated.is_empty() {
+                rules.repeated = Some(repeated);
+            }
+
+            paths.insert(path, rules);
+        }
+
+        common_fields.sort_by(|a, b| a.path.cmp(&b.path));
+        Self {
+            rows: profile.rows,
+            common_fields,
+            paths,
+        }
+    }
+
+    /// 检查一行
+    fn check_line(&self, line_num: usize, line: &str, findings: &mut Findings) {
+        let map = match serde_json::from_str::<JsonValue>(line) {
+            Ok(JsonValue::Object(map)) => map,
+            _ => {
+                findings.record(IssueKind::Unparseable, "", line_num);
+                return;
+            }
+        };
+
+        for field in &self.common_fields {
+            if !map.contains_key(&field.key) {
+                findings.record(IssueKind::MissingField, &field.path, line_num);
+            }
+        }
+
+        let mut path = String::new();
+        for (key, value) in &map {
+            path.clear();
+            push_key(&mut path, key);
+            if let (JsonValue::String(text), Some(repeated)) = (value, self.paths.get(&path).and_then(|r| r.repeated.as_ref())) {
+                if text.chars().count() >= MIN_REPEAT_CHARS && repeated.contains_key(&hash_text(text)) {
+                    findings.record(IssueKind::RepeatedValue, &path, line_num);
+                }
+            }
+            walk_paths(value, &mut path, &mut |path, value| self.check(path, value, line_num, findings));
+        }
+    }
+
+    fn check(&self, path: &str, value: &JsonValue, line_num: usize, findings: &mut Findings) {
+        if let Some(rules) = self.paths.get(path) {
+            if let Some(dominant) = rules.dominant_type {
+                if !value.is_null() && type_group(value) != dominant {
+                    findings.record(IssueKind::TypeMismatch, path, line_num);
+                }
+            }
+            if let JsonValue::String(text) = value {
+                let len = text.chars().count();
+                if text.trim().is_empty() {
+                    findings.record(IssueKind::EmptyString, path, line_num);
+                } else if rules.long_over.is_some_and(|limit| len > limit) {
+                    findings.record(IssueKind::TooLong, path, line_num);
+                } else if rules.short_under.is_some_and(|limit| len < limit) {
+                    findings.record(IssueKind::TooShort, path, line_num);
+                }
+            }
+        }
+    }
+
+    /// 问题的说明
+    fn detail(&self, kind: IssueKind, path: &str) -> String {
+        let rules = self.paths.get(path);
+        match kind {
+            IssueKind::Unparseable => "无法解析为 JSON 对象".to_string(),
+            IssueKind::MissingField => {
+                let present = self.common_fields.iter().find(|f| f.path == path).map(|f| f.present_rows).unwrap_or(0);
+                format!("{}/{} 行含有该字段", present, self.rows)
+            }
+            IssueKind::EmptyString => "空字符串或只含空白".to_string(),
+            IssueKind::TypeMismatch => format!("主要类型为 {}", rules.and_then(|r| r.dominant_type).unwrap_or("-")),
+            IssueKind::TooLong => format!("长度超过 {} 个字符", rules.and_then(|r| r.long_over).unwrap_or(0)),
+            IssueKind::TooShort => format!("长度少于 {} 个字符", rules.and_then(|r| r.short_under).unwrap_or(0)),
+            IssueKind::RepeatedValue => format!(
+                "{} 个不同的值各出现至少 {} 次",
+                rules.and_then(|r| r.repeated.as_ref()).map(|r| r.len()).unwrap_or(0),
+                REPEAT_MIN_COUNT
+            ),
+        }
+    }
+}
+
+/// 质量检查结果
+pub struct QualityAnalysis {
+    /// 文件路径
+    pub path: String,
+
+    /// 报告
+    pub report: QualityReport,
+
+    /// 每个问题的行号（最多 `MAX_ISSUE_LINES` 个），与 `report.issues` 一一对应
+    pub issue_lines: Vec<Vec<usize>>,
+}
+
+impl QualityAnalysis {
+    /// 两遍并行扫描：先统计各字段的概况，再按概况得出的规则逐行检查
+    pub fn analyze<F>(path: &str, line_offsets: &[u64], progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let lines = MappedLines::open(path, line_offsets)?;
+        let total = lines.len();
+        let report_progress = |stage: &str, done: usize, base: f32| {
+            progress_callback(LoadingProgress {
+                current: done,
+                total,
+                stage: stage.to_string(),
+                percentage: base + done as f32 / total.max(1) as f32 * 50.0,
+            });
+        };
+
+        let mut profile = Profile::default();
+        let mut start = 0;
+        while start < total {
+            let end = (start + BATCH_LINES).min(total);
+            let partial = (start..end)
+                .into_par_iter()
+                .fold(Profile::default, |mut acc, line_num| {
+                    if let Some(line) = lines.line(line_num).filter(|line| !line.is_empty()) {
+                        acc.add_line(line_num, line);
+                    }
+                    acc
+                })
+                .reduce(Profile::default, Profile::merge);
+            profile = profile.merge(partial);
+            start = end;
+            report_progress("统计字段概况", start, 0.0);
+        }
+
+        let rows = profile.rows;
+        let rules = Rules::from_profile(profile);
+
+        let mut findings = Findings::default();
+        let mut start = 0;
+        while start < total {
+            let end = (start + BATCH_LINES).min(total);
+            let partial = (start..end)
+                .into_par_iter()
+                .fold(Findings::default, |mut acc, line_num| {
+                    if let Some(line) = lines.line(line_num).filter(|line| !line.is_empty()) {
+                        rules.check_line(line_num, line, &mut acc);
+                    }
+                    acc
+                })
+                .reduce(Findings::default, Findings::merge);
+            findings = findings.merge(partial);
+            start = end;
+            report_progress("检查数据质量", start, 50.0);
+        }
+
+        let mut issues: Vec<((IssueKind, String), IssueAccumulator)> = findings.issues.into_iter().collect();
+        issues.sort_by(|a, b| a.0 .0.cmp(&b.0 .0).then(b.1.count.cmp(&a.1.count)).then(a.0 .1.cmp(&b.0 .1)));
+
+        let mut report_issues = Vec::with_capacity(issues.len());
+        let mut issue_lines = Vec::with_capacity(issues.len());
+        for ((kind, field), mut issue) in issues {
+            issue.lines.sort_unstable();
+            issue.lines.truncate(MAX_ISSUE_LINES);
+            report_issues.push(QualityIssue {
+                kind: kind.name().to_string(),
+                detail: rules.detail(kind, &field),
+                path: field,
+                count: issue.count,
+                sample_lines: issue.lines.iter().take(SAMPLE_LINES).copied().collect(),
+            });
+            issue_lines.push(issue.lines);
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            report: QualityReport {
+                total_lines: total,
+                rows,
+                issues: report_issues,
+            },
+            issue_lines,
+        })
+    }
+}
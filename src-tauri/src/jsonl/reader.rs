@@ -1,9 +1,14 @@
-use crate::models::{AppResult, AppError, JsonObject, PagedResponse, LoadingProgress};
+use crate::models::{AppResult, AppError, JsonObject, JsonValue, PagedResponse, LoadingProgress, RowError, ExportSummary, SearchProgress, IndexStatus, SortStatus, DedupSummary, DuplicateGroup, DuplicateGroupPage, NearDupSummary, SchemaReport, FieldStatsReport, HistogramData, CategoryData, TimeSeriesData, ScatterData, ValidationSummary, ViolationPage, QualityReport, DiffSummary, DiffPage, RowRef, RowComparison, EditStatus, EditHistory, EditHistoryEntry, SaveSummary, DraftStatus, RebaseSummary};
+use crate::jsonl::{RowTransform, SearchSession, InvertedIndex, FuzzyMatcher, SortOrder, DedupAnalysis, DedupMode, NearDupAnalysis, FileFingerprint, RowValidator, SchemaValidation, SchemaExportOptions, QualityAnalysis, EditJournal, EditOp, RowId, StagedSave, EditDraft, DraftSchedule, FieldValue, RowFilter, JsonPath, FlattenOptions, FileDiff, DiffKind, compare_rows, read_file_line, row_bytes, schema_document, flatten_row, flat_columns, extend_columns, line_matches, find_match_locations, hash128, fuzzy_scan, sort_by_score, infer_schema, field_statistics, histogram, category_counts, time_series, scatter_sample, MappedLines, TimeInterval};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use tokio::sync::Mutex as TokioMutex;
+use lru::LruCache;
 use std::time::{Duration, Instant};
 use rayon::prelude::*;
 
@@ -15,8 +20,26 @@ pub struct JsonlReader {
     /// 文件总行数
     total_lines: usize,
     
+    /// 每行的起始字节偏移，用于按行号随机读取
+    line_offsets: Vec<u64>,
+    
     /// 文件句柄缓存
     file_handle: Arc<Mutex<Option<BufReader<File>>>>,
+    
+    /// 当前的排序方式，设置后分页按排序后的顺序返回
+    sort_order: Option<SortOrder>,
+    
+    /// 设置排序时的编辑版本
+    sort_revision: u64,
+    
+    /// 尚未保存的编辑
+    journal: EditJournal,
+    
+    /// 编辑版本，每次修改编辑日志时递增
+    edit_revision: u64,
+    
+    /// 建立行索引时的文件指纹，编辑基于该版本的内容
+    fingerprint: Option<FileFingerprint>,
 }
 
 impl JsonlReader {
@@ -42,7 +65,13 @@ impl JsonlReader {
         Ok(Self {
             path: path_str,
             total_lines: 0,
+            line_offsets: Vec::new(),
             file_handle: Arc::new(Mutex::new(Some(reader))),
+            sort_order: None,
+            sort_revision: 0,
+            journal: EditJournal::default(),
+            edit_revision: 0,
+            fingerprint: None,
         })
     }
     
@@ -53,6 +82,8 @@ impl JsonlReader {
     {
         println!("计算文件总行数...");
         
+        self.fingerprint = Some(FileFingerprint::of(&self.path)?);
+        
         // 估算文件大小和总行数
         let metadata = std::fs::metadata(&self.path)?;
         let file_size = metadata.len();
@@ -64,6 +95,7 @@ impl JsonlReader {
         let mut line_count = 0;
         let mut bytes_read = 0;
         let mut buffer = String::new();
+        let mut line_offsets = Vec::with_capacity(estimated_capacity(file_size));
         
         // 设置进度报告的时间间隔
         let progress_interval = Duration::from_millis(100);
@@ -87,6 +119,7 @@ impl JsonlReader {
             match reader.read_line(&mut buffer) {
                 Ok(0) => break, // 文件结束
                 Ok(bytes) => {
+                    line_offsets.push(bytes_read);
                     line_count += 1;
                     bytes_read += bytes as u64;
                     
@@ -135,8 +168,9 @@ impl JsonlReader {
         
         println!("文件总行数: {}", line_count);
         
-        // 更新总行数
+        // 更新总行数与行偏移索引
         self.total_lines = line_count;
+        self.line_offsets = line_offsets;
         
         Ok(line_count)
     }
@@ -146,17 +180,265 @@ impl JsonlReader {
         self.total_lines
     }
     
+    /// 获取文件路径
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    
+    /// 每行的起始字节偏移
+    pub fn line_offsets(&self) -> &[u64] {
+        &self.line_offsets
+    }
+    
+    /// 建立行索引时的文件指纹，尚未建立时为空
+    pub fn fingerprint(&self) -> Option<FileFingerprint> {
+        self.fingerprint
+    }
+    
+    /// 打开建立行索引时的文件内容
+    ///
+    /// 文件被其他程序以替换的方式修改后，已打开的句柄仍指向原来的内容；
+    /// 原内容已被就地改写时返回保存冲突。
+    pub fn base_file(&self) -> AppResult<File> {
+        let mut file = {
+            let guard = self.file_handle.lock().map_err(|_| {
+                AppError::General("获取文件锁失败".to_string())
+            })?;
+            let reader = guard.as_ref().ok_or_else(|| {
+                AppError::General("文件句柄未初始化".to_string())
+            })?;
+            reader.get_ref().try_clone()?
+        };
+        if self.fingerprint != Some(FileFingerprint::of_file(&mut file)?) {
+            return Err(AppError::SaveConflict("原文件已被其他程序改写，原内容无法读取，请将编辑移植到文件的新内容后再保存".to_string()));
+        }
+        Ok(file)
+    }
+    
+    /// 关闭文件句柄，替换文件前调用
+    pub fn close_file(&self) {
+        if let Ok(mut guard) = self.file_handle.lock() {
+            *guard = None;
+        }
+    }
+    
+    /// 重新打开文件句柄
+    pub fn reopen_file(&self) -> AppResult<()> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut guard = self.file_handle.lock().map_err(|_| {
+            AppError::General("获取文件锁失败".to_string())
+        })?;
+        *guard = Some(reader);
+        Ok(())
+    }
+    
+    /// 尚未保存的编辑
+    pub fn journal(&self) -> &EditJournal {
+        &self.journal
+    }
+    
+    /// 尚未保存的编辑（可修改）
+    pub fn journal_mut(&mut self) -> &mut EditJournal {
+        self.edit_revision += 1;
+        &mut self.journal
+    }
+    
+    /// 编辑版本，用于判断编辑日志在某一时刻之后是否被修改过
+    pub fn edit_revision(&self) -> u64 {
+        self.edit_revision
+    }
+    
+    /// 当前的行数（包括尚未保存的增删行）
+    pub fn row_count(&self) -> usize {
+        self.journal.row_count(self.total_lines)
+    }
+    
+    /// 当前某一位置上的行
+    pub fn row_id_at(&self, position: usize) -> AppResult<RowId> {
+        self.journal.row_at(position, self.total_lines).ok_or(AppError::PageOutOfRange)
+    }
+    
+    /// 读取当前某一位置上的行并应用编辑，行不是 JSON 对象时返回错误
+    pub fn edited_row_at(&self, position: usize) -> AppResult<JsonValue> {
+        let row_id = self.row_id_at(position)?;
+        let mut row = match row_id {
+            RowId::Original(line_num) => {
+                let line = self.read_line_at(line_num)?;
+                serde_json::from_str::<JsonValue>(&line)
+                    .map_err(|e| AppError::General(format!("第 {} 行无法解析为 JSON: {}", position, e)))?
+            }
+            RowId::Inserted(index) => self.journal.inserted_content(index).cloned().unwrap_or_default(),
+        };
+        if !row.is_object() {
+            return Err(AppError::General(format!("第 {} 行不是 JSON 对象", position)));
+        }
+        self.journal.apply(row_id, &mut row)?;
+        Ok(row)
+    }
+    
+    /// 设置或清除排序方式
+    pub fn set_sort_order(&mut self, order: Option<SortOrder>) {
+        self.sort_order = order;
+        self.sort_revision = self.edit_revision;
+    }
+    
+    /// 当前排序状态
+    pub fn sort_status(&self) -> Option<SortStatus> {
+        self.sort_order.as_ref().map(|order| SortStatus {
+            key_path: order.key_path().to_string(),
+            descending: order.descending(),
+            total: order.len(),
+            stale: self.sort_revision != self.edit_revision,
+        })
+    }
+    
+    /// 读取指定行号（从0开始）的原始内容，已去除首尾空白
+    pub fn read_line_at(&self, line_num: usize) -> AppResult<String> {
+        let offset = *self.line_offsets.get(line_num).ok_or(AppError::PageOutOfRange)?;
+        
+        let mut handle_guard = self.file_handle.lock().map_err(|_| {
+            AppError::General("获取文件锁失败".to_string())
+        })?;
+        
+        let reader = handle_guard.as_mut().ok_or_else(|| {
+            AppError::General("文件句柄未初始化".to_string())
+        })?;
+        
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        
+        Ok(line.trim().to_string())
+    }
+    
+    /// 按行号读取若干行并组成分页数据
+    pub fn load_lines(&self, lines: &[usize], total: usize, transform: Option<&RowTransform>) -> AppResult<PagedResponse> {
+        let mut data = Vec::with_capacity(lines.len());
+        let mut loaded = Vec::with_capacity(lines.len());
+        let mut errors = Vec::new();
+        
+        for &line_num in lines {
+            let line = self.read_line_at(line_num)?;
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JsonObject>(&line) {
+                Ok(obj) => {
+                    self.push_row(&mut data, &mut errors, obj, RowId::Original(line_num), line_num, transform);
+                    loaded.push(line_num);
+                }
+                Err(e) => {
+                    println!("JSON 解析错误: {}, 行内容: {}", e, line);
+                }
+            }
+        }
+        
+        Ok(PagedResponse {
+            data,
+            total,
+            lines: loaded,
+            errors,
+            ..Default::default()
+        })
+    }
+    
+    /// 按当前的行位置读取若干行并组成分页数据
+    ///
+    /// 存在尚未保存的增删行时位置按编辑后的行排列解释，否则与原文件行号相同。
+    pub fn load_rows(&self, positions: &[usize], total: usize, transform: Option<&RowTransform>) -> AppResult<PagedResponse> {
+        if !self.journal.has_row_changes() {
+            return self.load_lines(positions, total, transform);
+        }
+        
+        let mut data = Vec::with_capacity(positions.len());
+        let mut loaded = Vec::with_capacity(positions.len());
+        let mut errors = Vec::new();
+        
+        for &position in positions {
+            let row_id = self.row_id_at(position)?;
+            let obj = match row_id {
+                RowId::Original(line_num) => {
+                    let line = self.read_line_at(line_num)?;
+                    if line.is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<JsonObject>(&line) {
+                        Ok(obj) => obj,
+                        Err(e) => {
+                            println!("JSON 解析错误: {}, 行内容: {}", e, line);
+                            continue;
+                        }
+                    }
+                }
+                RowId::Inserted(index) => match self.journal.inserted_content(index) {
+                    Some(JsonValue::Object(map)) => map.clone().into_iter().collect(),
+                    _ => continue,
+                },
+            };
+            self.push_row(&mut data, &mut errors, obj, row_id, position, transform);
+            loaded.push(position);
+        }
+        
+        Ok(PagedResponse {
+            data,
+            total,
+            lines: loaded,
+            errors,
+            ..Default::default()
+        })
+    }
+    
+    /// 把原文件行号换算为当前的行位置，已删除的行为 `None`
+    pub fn positions_of_lines(&self, lines: &[usize]) -> Vec<Option<usize>> {
+        let Some(order) = self.journal.order() else {
+            return lines.iter().map(|&line_num| (line_num < self.total_lines).then_some(line_num)).collect();
+        };
+        
+        let mut positions: HashMap<usize, Option<usize>> = lines.iter().map(|&line_num| (line_num, None)).collect();
+        for (position, row_id) in order.iter().enumerate() {
+            if let RowId::Original(line_num) = row_id {
+                if let Some(slot) = positions.get_mut(line_num) {
+                    *slot = Some(position);
+                }
+            }
+        }
+        lines.iter().map(|line_num| positions[line_num]).collect()
+    }
+    
+    /// 按原文件行号读取若干行，返回的是当前的行位置，已删除的行被跳过
+    pub fn load_original_lines(&self, lines: &[usize], total: usize, transform: Option<&RowTransform>) -> AppResult<PagedResponse> {
+        if !self.journal.has_row_changes() {
+            return self.load_lines(lines, total, transform);
+        }
+        let positions: Vec<usize> = self.positions_of_lines(lines).into_iter().flatten().collect();
+        self.load_rows(&positions, total, transform)
+    }
+    
     /// 加载指定页的数据
-    pub fn load_page(&self, page: usize, page_size: usize) -> AppResult<PagedResponse> {
+    pub fn load_page(&self, page: usize, page_size: usize, transform: Option<&RowTransform>) -> AppResult<PagedResponse> {
+        let row_count = self.row_count();
+        
         // 计算起始行和结束行
         let start_line = (page - 1) * page_size;
-        let end_line = std::cmp::min(start_line + page_size, self.total_lines);
+        let end_line = std::cmp::min(start_line + page_size, row_count);
         
         // 页码超出范围
-        if start_line >= self.total_lines {
+        if start_line >= row_count {
             return Err(AppError::PageOutOfRange);
         }
         
+        // 已排序时按排列读取，排序与增删行互斥，排列中的行号即行位置
+        if let Some(order) = &self.sort_order {
+            let lines = order.lines(start_line, end_line)?;
+            return self.load_lines(&lines, self.total_lines, transform);
+        }
+        
+        // 有增删行时按编辑后的行排列读取
+        if self.journal.has_row_changes() {
+            let positions: Vec<usize> = (start_line..end_line).collect();
+            return self.load_rows(&positions, row_count, transform);
+        }
+        
         // 获取文件句柄
         let mut handle_guard = self.file_handle.lock().map_err(|_| {
             AppError::General("获取文件锁失败".to_string())
@@ -166,18 +448,14 @@ impl JsonlReader {
             AppError::General("文件句柄未初始化".to_string())
         })?;
         
-        // 重置文件指针
-        reader.seek(SeekFrom::Start(0))?;
-        
-        // 跳过起始行之前的数据
-        for _ in 0..start_line {
-            let mut line = String::new();
-            reader.read_line(&mut line)?;
-        }
+        // 直接定位到起始行
+        reader.seek(SeekFrom::Start(self.line_offsets[start_line]))?;
         
         // 读取指定范围的行
         let mut data = Vec::with_capacity(end_line - start_line);
-        for _ in start_line..end_line {
+        let mut lines = Vec::with_capacity(end_line - start_line);
+        let mut errors = Vec::new();
+        for line_num in start_line..end_line {
             let mut line = String::new();
             match reader.read_line(&mut line) {
                 Ok(0) => break, // 文件结束
@@ -185,7 +463,10 @@ impl JsonlReader {
                     let line = line.trim();
                     if !line.is_empty() {
                         match serde_json::from_str::<JsonObject>(line) {
-                            Ok(obj) => data.push(obj),
+                            Ok(obj) => {
+                                self.push_row(&mut data, &mut errors, obj, RowId::Original(line_num), line_num, transform);
+                                lines.push(line_num);
+                            }
                             Err(e) => {
                                 println!("JSON 解析错误: {}, 行内容: {}", e, line);
                             }
@@ -199,77 +480,282 @@ impl JsonlReader {
         Ok(PagedResponse {
             data,
             total: self.total_lines,
+            lines,
+            errors,
+            ..Default::default()
         })
     }
     
-    /// 搜索关键词并返回匹配的分页数据
-    pub fn search(&self, keyword: &str, page: usize, page_size: usize) -> AppResult<PagedResponse> {
-        let keyword = keyword.to_lowercase();
+    /// 将一行数据加入当前页，先应用尚未保存的编辑，必要时再应用变换程序
+    ///
+    /// `line_num` 为该行在页面中显示的行号。变换失败的行以空对象占位，并在 `errors` 中记录原因。
+    fn push_row(
+        &self,
+        data: &mut Vec<JsonObject>,
+        errors: &mut Vec<RowError>,
+        obj: JsonObject,
+        row_id: RowId,
+        line_num: usize,
+        transform: Option<&RowTransform>,
+    ) {
+        let obj = if self.journal.is_edited(row_id) {
+            let mut row = JsonValue::Object(obj.into_iter().collect());
+            if let Err(e) = self.journal.apply(row_id, &mut row) {
+                println!("应用编辑失败: 行号={}, 错误={}", line_num, e);
+            }
+            match row {
+                JsonValue::Object(map) => map.into_iter().collect(),
+                _ => JsonObject::new(),
+            }
+        } else {
+            obj
+        };
         
-        // 获取文件句柄
-        let mut handle_guard = self.file_handle.lock().map_err(|_| {
-            AppError::General("获取文件锁失败".to_string())
-        })?;
+        let Some(transform) = transform else {
+            data.push(obj);
+            return;
+        };
         
-        let reader = handle_guard.as_mut().ok_or_else(|| {
-            AppError::General("文件句柄未初始化".to_string())
-        })?;
+        match transform.apply_to_object(obj) {
+            Ok(row) => data.push(row),
+            Err(message) => {
+                errors.push(RowError {
+                    index: data.len(),
+                    line: line_num,
+                    message,
+                });
+                data.push(JsonObject::new());
+            }
+        }
+    }
+    
+    /// 导出数据到新文件
+    ///
+    /// 导出的是应用尚未保存的编辑后的行。可按关键词过滤，并对每行应用变换程序；变换失败的行会被跳过。
+    pub fn export<P: AsRef<Path>>(
+        &self,
+        output: P,
+        keyword: Option<&str>,
+        transform: Option<&RowTransform>,
+    ) -> AppResult<ExportSummary> {
+        let keyword = keyword
+            .map(|k| k.to_lowercase())
+            .filter(|k| !k.is_empty());
         
-        // 重置文件指针
-        reader.seek(SeekFrom::Start(0))?;
+        // 使用独立的文件映射，避免阻塞分页读取
+        let mapped = MappedLines::open(&self.path, &self.line_offsets)?;
+        let mut writer = BufWriter::new(File::create(output.as_ref())?);
         
-        // 扫描整个文件，查找匹配的行
-        let mut matching_lines = Vec::new();
-        let mut line_num = 0;
+        let mut written = 0;
+        let mut skipped = 0;
+        let mut errors = Vec::new();
         
-        let mut line = String::new();
-        while reader.read_line(&mut line)? > 0 {
+        for line_num in 0..self.row_count() {
+            let row = row_bytes(&mapped, &self.journal, self.row_id_at(line_num)?)?;
+            let line = String::from_utf8_lossy(&row);
             let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                // 检查行是否包含关键词
-                if trimmed.to_lowercase().contains(&keyword) {
-                    matching_lines.push((line_num, trimmed.to_string()));
+            let matched = !trimmed.is_empty()
+                && keyword.as_ref().is_none_or(|k| line_matches(trimmed, k));
+            
+            if matched {
+                let output_line = match transform {
+                    None => Ok(trimmed.to_string()),
+                    Some(transform) => serde_json::from_str::<JsonValue>(trimmed)
+                        .map_err(|e| format!("JSON 解析错误: {}", e))
+                        .and_then(|value| transform.apply(value))
+                        .and_then(|value| serde_json::to_string(&value).map_err(|e| e.to_string())),
+                };
+                
+                match output_line {
+                    Ok(output_line) => {
+                        writeln!(writer, "{}", output_line)?;
+                        written += 1;
+                    }
+                    Err(message) => {
+                        skipped += 1;
+                        // 只保留前若干条错误，避免结果过大
+                        if errors.len() < 100 {
+                            errors.push(RowError {
+                                index: written + skipped - 1,
+                                line: line_num,
+                                message,
+                            });
+                        }
+                    }
                 }
             }
-            line_num += 1;
-            line.clear();
         }
         
-        // 计算分页
-        let total_matches = matching_lines.len();
-        let start_idx = (page - 1) * page_size;
-        let end_idx = std::cmp::min(start_idx + page_size, total_matches);
+        writer.flush()?;
         
-        // 页码超出范围
-        if start_idx >= total_matches && total_matches > 0 {
-            return Err(AppError::PageOutOfRange);
-        }
+        Ok(ExportSummary {
+            path: output.as_ref().display().to_string(),
+            written,
+            skipped,
+            errors,
+        })
+    }
+    
+    /// 导出除原文件中指定行以外的所有行，未被编辑的行保持原始内容不变
+    pub fn export_without<P: AsRef<Path>>(&self, output: P, dropped: &HashSet<usize>) -> AppResult<ExportSummary> {
+        let mapped = MappedLines::open(&self.path, &self.line_offsets)?;
+        let mut writer = BufWriter::new(File::create(output.as_ref())?);
         
-        // 提取当前页的数据
-        let mut data = Vec::with_capacity(end_idx - start_idx);
-        for i in start_idx..end_idx {
-            let (_, line) = &matching_lines[i];
-            let obj: JsonObject = serde_json::from_str(line)?;
-            data.push(obj);
+        let mut written = 0;
+        let mut skipped = 0;
+        
+        for position in 0..self.row_count() {
+            let row_id = self.row_id_at(position)?;
+            if matches!(row_id, RowId::Original(line_num) if dropped.contains(&line_num)) {
+                skipped += 1;
+                continue;
+            }
+            let row = row_bytes(&mapped, &self.journal, row_id)?;
+            let end = row.iter().rposition(|&b| b != b'\r' && b != b'\n').map_or(0, |i| i + 1);
+            writer.write_all(&row[..end])?;
+            writer.write_all(b"\n")?;
+            written += 1;
         }
         
-        Ok(PagedResponse {
-            data,
-            total: total_matches,
+        writer.flush()?;
+        
+        Ok(ExportSummary {
+            path: output.as_ref().display().to_string(),
+            written,
+            skipped,
+            errors: Vec::new(),
         })
     }
 }
 
+/// 按文件大小估算行偏移索引的初始容量
+fn estimated_capacity(file_size: u64) -> usize {
+    // 假设平均每行100字节，并限制预分配上限
+    ((file_size / 100) as usize).min(1 << 24)
+}
+
+/// 缓存结构推断结果的文件数量
+const SCHEMA_CACHE_SIZE: usize = 8;
+
+/// 展开视图生成列名时，没有可用的结构推断缓存则抽样推断的行数
+const FLAT_SCHEMA_SAMPLE: usize = 10_000;
+
 /// 全局 JSONL 读取器管理器
 pub struct JsonlReaderManager {
-    current_reader: TokioMutex<Option<JsonlReader>>,
+    current_reader: Arc<TokioMutex<Option<JsonlReader>>>,
+    
+    /// 当前搜索会话
+    search_session: Mutex<Option<Arc<SearchSession>>>,
+    
+    /// 下一个搜索会话编号
+    next_search_id: AtomicU64,
+    
+    /// 搜索索引存放目录
+    index_dir: Mutex<Option<PathBuf>>,
+    
+    /// 编辑草稿存放目录
+    draft_dir: Arc<Mutex<Option<PathBuf>>>,
+    
+    /// 等待写入的草稿
+    draft_schedule: Arc<DraftSchedule>,
+    
+    /// 当前文件的搜索索引
+    search_index: Arc<Mutex<Option<Arc<InvertedIndex>>>>,
+    
+    /// 正在进行的索引构建的取消标记
+    index_build: Arc<Mutex<Option<Arc<AtomicBool>>>>,
+    
+    /// 最近一次模糊搜索的结果，翻页时复用
+    fuzzy_cache: Mutex<Option<FuzzyCache>>,
+    
+    /// 最近一次重复检测的结果
+    dedup_analysis: Mutex<Option<Arc<DedupAnalysis>>>,
+    
+    /// 最近一次近似重复检测的结果
+    near_dup_analysis: Mutex<Option<Arc<NearDupAnalysis>>>,
+    
+    /// 按文件路径缓存的结构推断结果
+    schema_cache: Mutex<LruCache<String, CachedSchema>>,
+    
+    /// 最近一次 JSON Schema 校验的结果
+    validation: Mutex<Option<Arc<SchemaValidation>>>,
+    
+    /// 最近一次数据质量检查的结果
+    quality_analysis: Mutex<Option<Arc<QualityAnalysis>>>,
+    
+    /// 最近一次文件比较的结果
+    file_diff: Mutex<Option<Arc<FileDiff>>>,
+}
+
+/// 结构推断结果缓存
+struct CachedSchema {
+    /// 推断时的文件指纹
+    fingerprint: FileFingerprint,
+    
+    /// 推断结果
+    report: Arc<SchemaReport>,
+}
+
+/// 模糊搜索结果缓存
+struct FuzzyCache {
+    /// 文件路径
+    path: String,
+    
+    /// 查询关键词
+    keyword: String,
+    
+    /// 最大编辑距离
+    max_distance: Option<usize>,
+    
+    /// 按得分排序的 `(行位置, 得分)`，没有增删行时行位置即行号
+    results: Arc<Vec<(usize, f32)>>,
 }
 
 impl JsonlReaderManager {
     /// 创建新的管理器
     pub fn new() -> Self {
         Self {
-            current_reader: TokioMutex::new(None),
+            current_reader: Arc::new(TokioMutex::new(None)),
+            search_session: Mutex::new(None),
+            next_search_id: AtomicU64::new(1),
+            index_dir: Mutex::new(None),
+            draft_dir: Arc::new(Mutex::new(None)),
+            draft_schedule: Arc::new(DraftSchedule::default()),
+            search_index: Arc::new(Mutex::new(None)),
+            index_build: Arc::new(Mutex::new(None)),
+            fuzzy_cache: Mutex::new(None),
+            dedup_analysis: Mutex::new(None),
+            near_dup_analysis: Mutex::new(None),
+            schema_cache: Mutex::new(LruCache::new(NonZeroUsize::new(SCHEMA_CACHE_SIZE).unwrap())),
+            validation: Mutex::new(None),
+            quality_analysis: Mutex::new(None),
+            file_diff: Mutex::new(None),
+        }
+    }
+    
+    /// 设置搜索索引存放目录
+    pub fn set_index_dir(&self, dir: PathBuf) {
+        if let Ok(mut guard) = self.index_dir.lock() {
+            *guard = Some(dir);
+        }
+    }
+    
+    /// 设置编辑草稿存放目录，首次设置时启动草稿写入线程
+    pub fn set_draft_dir(&self, dir: PathBuf) {
+        let first = match self.draft_dir.lock() {
+            Ok(mut guard) => guard.replace(dir).is_none(),
+            Err(_) => return,
+        };
+        if first {
+            self.spawn_draft_writer();
+        }
+    }
+    
+    /// 立即写入当前文件尚未写入的草稿，应用退出前调用
+    pub fn flush_pending_draft(&self) {
+        let guard = self.current_reader.blocking_lock();
+        if let Some(reader) = guard.as_ref() {
+            self.flush_draft(reader);
         }
     }
     
@@ -277,6 +763,11 @@ impl JsonlReaderManager {
     pub async fn set_reader(&self, path: &str) -> AppResult<()> {
         let reader = JsonlReader::new(path)?;
         let mut guard = self.current_reader.lock().await;
+        if let Some(previous) = guard.as_ref() {
+            self.flush_draft(previous);
+        }
+        self.cancel_search();
+        self.load_search_index(path);
         *guard = Some(reader);
         Ok(())
     }
@@ -294,34 +785,1650 @@ impl JsonlReaderManager {
         
         // 设置为当前读取器
         let mut guard = self.current_reader.lock().await;
+        if let Some(previous) = guard.as_ref() {
+            self.flush_draft(previous);
+        }
+        self.cancel_search();
+        self.load_search_index(path);
         *guard = Some(reader);
         
         Ok(())
     }
     
-    /// 获取分页数据
-    pub async fn load_page(&self, page: usize, page_size: usize) -> AppResult<PagedResponse> {
-        let guard = self.current_reader.lock().await;
-        match &*guard {
-            Some(reader) => reader.load_page(page, page_size),
+    /// 按字段排序，之后的分页按排序结果返回
+    ///
+    /// 按应用编辑后的值排序；排序后的编辑不会移动行，排序状态标记为过期，重新排序即可更新。
+    pub async fn sort_by_field<F>(&self, key_path: &str, descending: bool, progress_callback: F) -> AppResult<SortStatus>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let (path, journal, revision) = {
+            let guard = self.current_reader.lock().await;
+            let reader = guard.as_ref()
+                .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+            if reader.journal().has_row_changes() {
+                return Err(AppError::General("有尚未保存的增删行，无法排序".to_string()));
+            }
+            let journal = EditJournal::from_snapshot(reader.journal().snapshot(), reader.total_lines());
+            (reader.path().to_string(), journal, reader.edit_revision())
+        };
+        
+        // 排序期间不占用读取器，分页仍可按原顺序进行
+        let start_time = Instant::now();
+        let order = SortOrder::build(&path, key_path, descending, &journal, progress_callback)?;
+        println!("排序完成: 字段={}, 降序={}, 行数={}, 耗时={:?}", key_path, descending, order.len(), start_time.elapsed());
+        
+        let mut guard = self.current_reader.lock().await;
+        match guard.as_mut() {
+            Some(reader) if reader.path() == path => {
+                if reader.edit_revision() != revision {
+                    return Err(AppError::General("排序期间编辑发生了变化，请重新排序".to_string()));
+                }
+                if order.len() != reader.total_lines() {
+                    return Err(AppError::General("文件在排序期间发生了变化".to_string()));
+                }
+                reader.set_sort_order(Some(order));
+                reader.sort_status()
+                    .ok_or_else(|| AppError::General("设置排序失败".to_string()))
+            }
+            _ => Err(AppError::General("排序期间已切换文件".to_string())),
+        }
+    }
+    
+    /// 取消排序，恢复文件原始顺序
+    pub async fn clear_sort(&self) -> AppResult<bool> {
+        let mut guard = self.current_reader.lock().await;
+        match guard.as_mut() {
+            Some(reader) => {
+                let sorted = reader.sort_status().is_some();
+                reader.set_sort_order(None);
+                Ok(sorted)
+            }
             None => Err(AppError::General("未打开JSONL文件".to_string())),
         }
     }
     
-    /// 搜索关键词
-    pub async fn search(&self, keyword: &str, page: usize, page_size: usize) -> AppResult<PagedResponse> {
+    /// 获取当前排序状态
+    pub async fn sort_status(&self) -> AppResult<Option<SortStatus>> {
         let guard = self.current_reader.lock().await;
         match &*guard {
-            Some(reader) => reader.search(keyword, page, page_size),
+            Some(reader) => Ok(reader.sort_status()),
             None => Err(AppError::General("未打开JSONL文件".to_string())),
         }
     }
     
+    /// 检测重复行
+    pub async fn analyze_duplicates(&self, mode: &str, key_paths: &[String]) -> AppResult<DedupSummary> {
+        let mode = DedupMode::parse(mode, key_paths)?;
+        
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let start_time = Instant::now();
+        let analysis = DedupAnalysis::analyze(reader.path(), reader.line_offsets(), mode)?;
+        println!("重复检测完成: 方式={}, 重复组={}, 耗时={:?}", analysis.mode.name(), analysis.groups.len(), start_time.elapsed());
+        
+        let summary = DedupSummary {
+            mode: analysis.mode.name().to_string(),
+            compared_lines: analysis.compared_lines,
+            duplicate_groups: analysis.groups.len(),
+            redundant_rows: analysis.redundant_rows(),
+        };
+        
+        if let Ok(mut cache) = self.dedup_analysis.lock() {
+            *cache = Some(Arc::new(analysis));
+        }
+        
+        Ok(summary)
+    }
+    
+    /// 获取当前文件最近一次的重复检测结果
+    fn current_dedup_analysis(&self, reader: &JsonlReader) -> AppResult<Arc<DedupAnalysis>> {
+        self.dedup_analysis.lock()
+            .ok()
+            .and_then(|cache| cache.clone())
+            .filter(|analysis| analysis.path == reader.path())
+            .ok_or_else(|| AppError::General("请先进行重复检测".to_string()))
+    }
+    
+    /// 分页获取重复组
+    pub async fn duplicate_groups(&self, page: usize, page_size: usize) -> AppResult<DuplicateGroupPage> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let analysis = self.current_dedup_analysis(reader)?;
+        
+        Self::group_page(reader, &analysis.groups, page, page_size)
+    }
+    
+    /// 从行号分组中取出一页，每组附带首行数据
+    fn group_page(reader: &JsonlReader, all_groups: &[Vec<usize>], page: usize, page_size: usize) -> AppResult<DuplicateGroupPage> {
+        // 单组最多返回的行号数量
+        const MAX_GROUP_LINES: usize = 1000;
+        
+        let total = all_groups.len();
+        let start_idx = (page - 1) * page_size;
+        let end_idx = std::cmp::min(start_idx + page_size, total);
+        
+        // 页码超出范围
+        if start_idx >= total && total > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        let mut groups = Vec::with_capacity(end_idx.saturating_sub(start_idx));
+        for group in all_groups.get(start_idx..end_idx).unwrap_or(&[]) {
+            let sample = reader.read_line_at(group[0])
+                .ok()
+                .and_then(|line| serde_json::from_str::<JsonObject>(&line).ok());
+            groups.push(DuplicateGroup {
+                lines: group.iter().take(MAX_GROUP_LINES).copied().collect(),
+                count: group.len(),
+                sample,
+            });
+        }
+        
+        Ok(DuplicateGroupPage { groups, total })
+    }
+    
+    /// 检测指定文本字段的近似重复行
+    pub async fn analyze_near_duplicates<F>(&self, field: &str, threshold: f32, progress_callback: F) -> AppResult<NearDupSummary>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let start_time = Instant::now();
+        let analysis = NearDupAnalysis::analyze(reader.path(), reader.line_offsets(), field, threshold, progress_callback)?;
+        println!("近似重复检测完成: 字段={}, 阈值={}, 簇数={}, 耗时={:?}", field, threshold, analysis.clusters.len(), start_time.elapsed());
+        
+        let summary = NearDupSummary {
+            field: analysis.field.clone(),
+            threshold: analysis.threshold,
+            compared_lines: analysis.compared_lines,
+            clusters: analysis.clusters.len(),
+            clustered_rows: analysis.clustered_rows(),
+        };
+        
+        if let Ok(mut cache) = self.near_dup_analysis.lock() {
+            *cache = Some(Arc::new(analysis));
+        }
+        
+        Ok(summary)
+    }
+    
+    /// 分页获取近似重复簇，每簇附带代表行
+    pub async fn near_duplicate_clusters(&self, page: usize, page_size: usize) -> AppResult<DuplicateGroupPage> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let analysis = self.near_dup_analysis.lock()
+            .ok()
+            .and_then(|cache| cache.clone())
+            .filter(|analysis| analysis.path == reader.path())
+            .ok_or_else(|| AppError::General("请先进行近似重复检测".to_string()))?;
+        
+        Self::group_page(reader, &analysis.clusters, page, page_size)
+    }
+    
+    /// 导出去重后的文件，每组保留第一行或最后一行
+    pub async fn export_deduplicated(&self, output: &str, keep_last: bool) -> AppResult<ExportSummary> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        // 重复分组按文件中已保存的内容计算，编辑后可能不再重复
+        if !reader.journal().is_empty() {
+            return Err(AppError::General("有尚未保存的编辑，请先保存后再导出去重结果".to_string()));
+        }
+        let analysis = self.current_dedup_analysis(reader)?;
+        
+        let dropped = analysis.rows_to_drop(keep_last);
+        reader.export_without(output, &dropped)
+    }
+    
+    /// 推断当前文件的字段结构，`sample_size` 为空时扫描全部行
+    ///
+    /// 结果按文件缓存，文件未变化且抽样方式相同时直接返回缓存。
+    pub async fn infer_schema<F>(&self, sample_size: Option<usize>, refresh: bool, progress_callback: F) -> AppResult<Arc<SchemaReport>>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let fingerprint = FileFingerprint::of(reader.path())?;
+        
+        if !refresh {
+            if let Ok(mut cache) = self.schema_cache.lock() {
+                if let Some(cached) = cache.get(reader.path()) {
+                    if cached.fingerprint == fingerprint && cached.report.sample_size == sample_size {
+                        return Ok(cached.report.clone());
+                    }
+                }
+            }
+        }
+        
+        let start_time = Instant::now();
+        let report = Arc::new(infer_schema(reader.path(), reader.line_offsets(), sample_size, progress_callback)?);
+        println!("结构推断完成: 行数={}, 字段数={}, 耗时={:?}", report.rows_scanned, report.fields.len(), start_time.elapsed());
+        
+        if let Ok(mut cache) = self.schema_cache.lock() {
+            cache.put(reader.path().to_string(), CachedSchema {
+                fingerprint,
+                report: report.clone(),
+            });
+        }
+        
+        Ok(report)
+    }
+    
+    /// 将推断出的字段结构导出为 JSON Schema 文件，返回 Schema 文档
+    pub async fn export_inferred_schema<F>(&self, output: &str, sample_size: Option<usize>, options: &SchemaExportOptions, progress_callback: F) -> AppResult<JsonValue>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let report = self.infer_schema(sample_size, false, progress_callback).await?;
+        let document = schema_document(&report, options);
+        
+        let text = serde_json::to_string_pretty(&document)
+            .map_err(|e| AppError::General(format!("序列化 JSON Schema 失败: {}", e)))?;
+        std::fs::write(output, text)?;
+        
+        Ok(document)
+    }
+    
+    /// 计算字段统计，`keyword` 非空时只统计匹配的行
+    pub async fn field_statistics<F>(&self, fields: &[String], keyword: Option<&str>, top_k: usize, progress_callback: F) -> AppResult<FieldStatsReport>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let keyword = keyword.filter(|k| !k.is_empty());
+        let start_time = Instant::now();
+        let lines = self.filtered_lines(reader, keyword)?;
+        let (rows, fields) = field_statistics(reader.path(), reader.line_offsets(), &lines, fields, top_k, progress_callback)?;
+        println!("字段统计完成: 行数={}, 字段数={}, 耗时={:?}", rows, fields.len(), start_time.elapsed());
+        
+        Ok(FieldStatsReport {
+            keyword: keyword.map(str::to_string),
+            rows,
+            fields,
+        })
+    }
+    
+    /// 数值字段的直方图，`keyword` 非空时只统计匹配的行
+    pub async fn chart_histogram(&self, field: &str, bins: usize, keyword: Option<&str>) -> AppResult<HistogramData> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let lines = self.filtered_lines(reader, keyword)?;
+        histogram(reader.path(), reader.line_offsets(), &lines, field, bins)
+    }
+    
+    /// 类别字段的计数，`keyword` 非空时只统计匹配的行
+    pub async fn chart_categories(&self, field: &str, limit: usize, keyword: Option<&str>) -> AppResult<CategoryData> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let lines = self.filtered_lines(reader, keyword)?;
+        category_counts(reader.path(), reader.line_offsets(), &lines, field, limit)
+    }
+    
+    /// 日期字段的时间序列，`keyword` 非空时只统计匹配的行
+    pub async fn chart_time_series(&self, field: &str, interval: &str, keyword: Option<&str>) -> AppResult<TimeSeriesData> {
+        let interval = TimeInterval::parse(interval)?;
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let lines = self.filtered_lines(reader, keyword)?;
+        time_series(reader.path(), reader.line_offsets(), &lines, field, interval)
+    }
+    
+    /// 两个数值字段的散点抽样，`keyword` 非空时只统计匹配的行
+    pub async fn chart_scatter(&self, x_field: &str, y_field: &str, limit: usize, keyword: Option<&str>) -> AppResult<ScatterData> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let lines = self.filtered_lines(reader, keyword)?;
+        scatter_sample(reader.path(), reader.line_offsets(), &lines, x_field, y_field, limit)
+    }
+    
+    /// 获取匹配关键词的全部行号，关键词为空时返回所有行
+    ///
+    /// 有可用索引时使用索引，否则并行扫描文件。
+    fn filtered_lines(&self, reader: &JsonlReader, keyword: Option<&str>) -> AppResult<Vec<usize>> {
+        let Some(keyword) = keyword.filter(|k| !k.is_empty()) else {
+            return Ok((0..reader.total_lines()).collect());
+        };
+        
+        if let Some(lines) = self.query_search_index(reader, keyword)? {
+            return Ok(lines);
+        }
+        
+        let keyword_lower = keyword.to_lowercase();
+        let mapped = MappedLines::open(reader.path(), reader.line_offsets())?;
+        Ok(mapped.par_filter_map(|line_num, line| line_matches(line, &keyword_lower).then_some(line_num)))
+    }
+    
+    /// 使用 JSON Schema 校验当前文件的所有行
+    pub async fn validate_with_schema<F>(&self, schema_path: &str, draft: Option<&str>, progress_callback: F) -> AppResult<ValidationSummary>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let validator = RowValidator::load(schema_path, draft)?;
+        
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let start_time = Instant::now();
+        let validation = SchemaValidation::run(reader.path(), reader.line_offsets(), schema_path, &validator, progress_callback)?;
+        println!("Schema 校验完成: 校验行数={}, 不合法行数={}, 耗时={:?}", validation.checked_lines, validation.invalid_lines.len(), start_time.elapsed());
+        
+        let summary = ValidationSummary {
+            schema_path: validation.schema_path.clone(),
+            checked_lines: validation.checked_lines,
+            invalid_rows: validation.invalid_lines.len(),
+            violations: validation.violations.len(),
+        };
+        
+        if let Ok(mut cache) = self.validation.lock() {
+            *cache = Some(Arc::new(validation));
+        }
+        
+        Ok(summary)
+    }
+    
+    /// 获取当前文件最近一次的校验结果
+    fn current_validation(&self, reader: &JsonlReader) -> AppResult<Arc<SchemaValidation>> {
+        self.validation.lock()
+            .ok()
+            .and_then(|cache| cache.clone())
+            .filter(|validation| validation.path == reader.path())
+            .ok_or_else(|| AppError::General("请先进行 JSON Schema 校验".to_string()))
+    }
+    
+    /// 分页获取违规列表
+    pub async fn schema_violations(&self, page: usize, page_size: usize) -> AppResult<ViolationPage> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let validation = self.current_validation(reader)?;
+        
+        let total = validation.violations.len();
+        let start_idx = (page - 1) * page_size;
+        let end_idx = std::cmp::min(start_idx + page_size, total);
+        
+        // 页码超出范围
+        if start_idx >= total && total > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        Ok(ViolationPage {
+            violations: validation.violations.get(start_idx..end_idx).unwrap_or(&[]).to_vec(),
+            total,
+        })
+    }
+    
+    /// 分页加载不合法的行
+    pub async fn load_invalid_page(&self, page: usize, page_size: usize, transform: Option<&str>) -> AppResult<PagedResponse> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let validation = self.current_validation(reader)?;
+        let transform = Self::compile_transform(transform)?;
+        
+        let total = validation.invalid_lines.len();
+        let start_idx = (page - 1) * page_size;
+        let end_idx = std::cmp::min(start_idx + page_size, total);
+        
+        // 页码超出范围
+        if start_idx >= total && total > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        let lines = validation.invalid_lines.get(start_idx..end_idx).unwrap_or(&[]);
+        reader.load_original_lines(lines, total, transform.as_ref())
+    }
+    
+    /// 检查当前文件的数据质量
+    pub async fn quality_report<F>(&self, progress_callback: F) -> AppResult<QualityReport>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let start_time = Instant::now();
+        let analysis = QualityAnalysis::analyze(reader.path(), reader.line_offsets(), progress_callback)?;
+        println!("数据质量检查完成: 问题数={}, 耗时={:?}", analysis.report.issues.len(), start_time.elapsed());
+        
+        let report = analysis.report.clone();
+        if let Ok(mut cache) = self.quality_analysis.lock() {
+            *cache = Some(Arc::new(analysis));
+        }
+        
+        Ok(report)
+    }
+    
+    /// 分页加载某个质量问题涉及的行，`issue` 为问题在报告中的下标
+    pub async fn load_quality_issue_page(&self, issue: usize, page: usize, page_size: usize, transform: Option<&str>) -> AppResult<PagedResponse> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let analysis = self.quality_analysis.lock()
+            .ok()
+            .and_then(|cache| cache.clone())
+            .filter(|analysis| analysis.path == reader.path())
+            .ok_or_else(|| AppError::General("请先进行数据质量检查".to_string()))?;
+        let lines = analysis.issue_lines.get(issue)
+            .ok_or_else(|| AppError::General(format!("质量问题不存在: {}", issue)))?;
+        let transform = Self::compile_transform(transform)?;
+        
+        let total = lines.len();
+        let start_idx = (page - 1) * page_size;
+        let end_idx = std::cmp::min(start_idx + page_size, total);
+        
+        // 页码超出范围
+        if start_idx >= total && total > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        let mut response = reader.load_original_lines(&lines[start_idx..end_idx], total, transform.as_ref())?;
+        // 无法解析的行不会出现在 `data` 中，仍通过行位置返回
+        if response.lines.is_empty() {
+            response.lines = reader.positions_of_lines(&lines[start_idx..end_idx]).into_iter().flatten().collect();
+        }
+        Ok(response)
+    }
+    
+    /// 比较两个 JSONL 文件，`key_field` 为空时按行号对齐，否则按该字段的值对齐
+    pub async fn diff_files<F>(&self, left_path: &str, right_path: &str, key_field: Option<&str>, progress_callback: F) -> AppResult<DiffSummary>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let start_time = Instant::now();
+        let diff = FileDiff::compare(left_path, right_path, key_field, progress_callback)?;
+        println!("文件比较完成: 新增={}, 删除={}, 修改={}, 耗时={:?}",
+                 diff.summary.added, diff.summary.removed, diff.summary.changed, start_time.elapsed());
+        
+        let summary = diff.summary.clone();
+        if let Ok(mut cache) = self.file_diff.lock() {
+            *cache = Some(Arc::new(diff));
+        }
+        
+        Ok(summary)
+    }
+    
+    /// 分页获取最近一次文件比较的差异，`kind` 为 added / removed / changed 时只返回该类型
+    pub async fn diff_page(&self, page: usize, page_size: usize, kind: Option<&str>) -> AppResult<DiffPage> {
+        let diff = self.file_diff.lock()
+            .map_err(|_| AppError::General("获取比较结果失败".to_string()))?
+            .clone()
+            .ok_or_else(|| AppError::General("请先比较文件".to_string()))?;
+        let kind = kind.filter(|k| !k.is_empty()).map(DiffKind::parse).transpose()?;
+        
+        let entries = diff.filtered(kind);
+        let total = entries.len();
+        let start_idx = (page - 1) * page_size;
+        let end_idx = std::cmp::min(start_idx + page_size, total);
+        
+        // 页码超出范围
+        if start_idx >= total && total > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        Ok(DiffPage {
+            entries: diff.details(entries.get(start_idx..end_idx).unwrap_or(&[]))?,
+            total,
+        })
+    }
+    
+    /// 比较任意两行，行可以来自不同文件
+    pub async fn diff_rows(&self, left: &RowRef, right: &RowRef) -> AppResult<RowComparison> {
+        let guard = self.current_reader.lock().await;
+        let read_row = |row: &RowRef| -> AppResult<JsonValue> {
+            let line = match (&*guard, row.path.as_deref()) {
+                (Some(reader), None) => reader.read_line_at(row.line)?,
+                (Some(reader), Some(path)) if path == reader.path() => reader.read_line_at(row.line)?,
+                (None, None) => return Err(AppError::General("未打开JSONL文件".to_string())),
+                (_, Some(path)) => read_file_line(path, row.line)?,
+            };
+            Ok(serde_json::from_str::<JsonValue>(&line).unwrap_or(JsonValue::String(line)))
+        };
+        
+        let left = read_row(left)?;
+        let right = read_row(right)?;
+        Ok(compare_rows(left, right))
+    }
+    
+    /// 当前编辑状态
+    fn edit_status_of(reader: &JsonlReader) -> EditStatus {
+        EditStatus {
+            pending_edits: reader.journal().len(),
+            edited_rows: reader.journal().edited_rows(),
+            bulk_edits: reader.journal().bulk_edits(),
+            inserted_rows: reader.journal().inserted_rows(),
+            deleted_rows: reader.journal().deleted_rows(reader.total_lines()),
+            total_rows: reader.row_count(),
+            can_undo: reader.journal().can_undo(),
+            can_redo: reader.journal().can_redo(),
+        }
+    }
+    
+    /// 设置某一行中指定字段的值，返回编辑后的整行
+    ///
+    /// 编辑只记录在编辑日志中，原文件在保存前保持不变。
+    pub async fn set_field_value(&self, line: usize, path: &str, value: JsonValue) -> AppResult<JsonObject> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let path = JsonPath::parse(path)?;
+        
+        let row_id = reader.row_id_at(line)?;
+        let mut row = reader.edited_row_at(line)?;
+        reader.journal_mut().set_value(row_id, &mut row, path, value)?;
+        self.edits_changed(reader);
+        
+        match row {
+            JsonValue::Object(map) => Ok(map.into_iter().collect()),
+            _ => Err(AppError::General(format!("第 {} 行不是 JSON 对象", line))),
+        }
+    }
+    
+    /// 编译批量操作的筛选条件
+    fn row_filter(keyword: Option<&str>, condition: Option<&str>) -> AppResult<RowFilter> {
+        Ok(RowFilter::new(keyword, Self::compile_transform(condition)?))
+    }
+    
+    /// 为所有行（或满足条件的行）添加字段
+    ///
+    /// `expression` 非空时对每一行求值 jq 表达式作为字段值，否则使用 `value`；
+    /// `overwrite` 为假时跳过已有该字段的行。
+    pub async fn add_field(
+        &self,
+        path: &str,
+        value: Option<JsonValue>,
+        expression: Option<&str>,
+        overwrite: bool,
+        keyword: Option<&str>,
+        condition: Option<&str>,
+    ) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let value = match Self::compile_transform(expression)? {
+            Some(transform) => FieldValue::Computed(Arc::new(transform)),
+            None => FieldValue::Literal(value.unwrap_or(JsonValue::Null)),
+        };
+        let filter = Self::row_filter(keyword, condition)?;
+        reader.journal_mut().add_field(JsonPath::parse(path)?, value, overwrite, filter)?;
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 删除所有行（或满足条件的行）中的字段
+    pub async fn remove_field(&self, path: &str, keyword: Option<&str>, condition: Option<&str>) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let filter = Self::row_filter(keyword, condition)?;
+        reader.journal_mut().remove_field(JsonPath::parse(path)?, filter)?;
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 重命名或移动所有行（或满足条件的行）中的字段
+    pub async fn rename_field(&self, from: &str, to: &str, keyword: Option<&str>, condition: Option<&str>) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let filter = Self::row_filter(keyword, condition)?;
+        reader.journal_mut().rename_field(JsonPath::parse(from)?, JsonPath::parse(to)?, filter)?;
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 增删行前检查当前是否可以改变行排列
+    fn check_row_changes_allowed(reader: &JsonlReader) -> AppResult<()> {
+        if reader.sort_status().is_some() {
+            return Err(AppError::General("排序状态下无法增删行，请先取消排序".to_string()));
+        }
+        Ok(())
+    }
+    
+    /// 在指定位置插入若干行，`position` 等于总行数时追加到末尾
+    pub async fn insert_rows(&self, position: usize, rows: Vec<JsonValue>) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        Self::check_row_changes_allowed(reader)?;
+        
+        let total_lines = reader.total_lines();
+        reader.journal_mut().insert_rows(position, rows, total_lines)?;
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 删除指定行号（当前的行位置）上的行
+    pub async fn delete_rows(&self, lines: &[usize]) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        Self::check_row_changes_allowed(reader)?;
+        
+        let total_lines = reader.total_lines();
+        reader.journal_mut().delete_rows(lines, total_lines)?;
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 复制指定行号上的行，副本插入在原行之后，内容为原行当前（已应用编辑）的内容
+    pub async fn duplicate_rows(&self, lines: &[usize]) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        Self::check_row_changes_allowed(reader)?;
+        
+        let rows = lines
+            .iter()
+            .map(|&line| reader.edited_row_at(line).map(|row| (line, row)))
+            .collect::<AppResult<Vec<_>>>()?;
+        let total_lines = reader.total_lines();
+        reader.journal_mut().duplicate_rows(rows, total_lines)?;
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 获取尚未保存的编辑状态
+    pub async fn edit_status(&self) -> AppResult<EditStatus> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 把尚未保存的编辑写入原文件
+    ///
+    /// 先写入同目录下的临时文件并同步到磁盘，再原子地替换原文件，写入中途出错不影响原文件；
+    /// `backup` 为真时把原文件保留为 `.bak`。原文件在编辑期间被其他程序修改时返回保存冲突，
+    /// 此时可以另存为新文件，或把编辑移植到文件的新内容上。
+    pub async fn save_edits<F>(&self, backup: bool, progress_callback: F) -> AppResult<SaveSummary>
+    where
+        F: Fn(LoadingProgress) + Send + Sync + 'static,
+    {
+        self.write_edits(None, backup, progress_callback).await
+    }
+    
+    /// 把编辑后的内容另存为新文件，之后打开新文件，原文件保持不变
+    pub async fn save_edits_as<F>(&self, target: &str, progress_callback: F) -> AppResult<SaveSummary>
+    where
+        F: Fn(LoadingProgress) + Send + Sync + 'static,
+    {
+        self.write_edits(Some(target), false, progress_callback).await
+    }
+    
+    /// 确认原文件与建立行索引时一致，否则返回保存冲突
+    fn check_unchanged(reader: &JsonlReader) -> AppResult<()> {
+        let fingerprint = FileFingerprint::of(reader.path())?;
+        if reader.fingerprint() != Some(fingerprint) {
+            return Err(AppError::SaveConflict("文件在编辑期间已被其他程序修改，可以另存为新文件，或将编辑移植到文件的新内容后再保存".to_string()));
+        }
+        Ok(())
+    }
+    
+    /// 把编辑后的内容写入 `target`（为空时写回原文件），完成后打开写入的文件
+    ///
+    /// 写入成功但重新打开失败时仍返回保存结果，错误记录在 `reopen_error` 中：
+    /// 另存为时保留当前文件与编辑，写回原文件时关闭当前文件。
+    async fn write_edits<F>(&self, target: Option<&str>, backup: bool, progress_callback: F) -> AppResult<SaveSummary>
+    where
+        F: Fn(LoadingProgress) + Send + Sync + 'static,
+    {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        if reader.journal().is_empty() {
+            return Err(AppError::General("没有需要保存的编辑".to_string()));
+        }
+        
+        let source_path = reader.path().to_string();
+        let target = target.unwrap_or(&source_path).to_string();
+        let in_place = target == source_path || std::fs::canonicalize(&target)
+            .is_ok_and(|target| std::fs::canonicalize(&source_path).is_ok_and(|source| source == target));
+        if in_place {
+            Self::check_unchanged(reader)?;
+        }
+        
+        let start_time = Instant::now();
+        let progress_callback = Arc::new(progress_callback);
+        let staged = {
+            let source = reader.base_file()?;
+            StagedSave::write(&source, reader.line_offsets(), reader.journal(), Path::new(&target), |progress| progress_callback(progress))?
+        };
+        let rows = staged.rows();
+        
+        if in_place {
+            // 写入期间文件可能被修改，替换前再确认一次
+            Self::check_unchanged(reader)?;
+            // Windows 下无法替换打开中的文件
+            reader.close_file();
+        }
+        let backup_path = match staged.commit(backup) {
+            Ok(backup_path) => backup_path,
+            Err(e) => {
+                if let Err(reopen_error) = reader.reopen_file() {
+                    println!("重新打开文件失败: {}", reopen_error);
+                }
+                return Err(e);
+            }
+        };
+        println!("保存编辑完成: 文件={}, 行数={}, 备份={:?}, 耗时={:?}", target, rows, backup_path, start_time.elapsed());
+        
+        // 先打开并索引写入的文件，成功后再替换当前文件，失败时不丢失当前会话
+        let reopened = JsonlReader::new(&target).and_then(|mut reader| {
+            reader.count_lines_with_progress(move |progress| progress_callback(progress))?;
+            Ok(reader)
+        });
+        let reopen_error = match reopened {
+            Ok(reader) => {
+                *guard = Some(reader);
+                None
+            }
+            Err(e) => {
+                println!("重新打开保存的文件失败: 文件={}, 错误={}", target, e);
+                if !in_place {
+                    // 另存为时原文件未变，保留当前文件与编辑，可以重试
+                    return Ok(SaveSummary {
+                        path: target,
+                        rows,
+                        backup_path: backup_path.map(|backup_path| backup_path.to_string_lossy().into_owned()),
+                        reopen_error: Some(e.to_string()),
+                    });
+                }
+                // 原文件已被替换，旧的行索引不再对应文件内容
+                guard.take();
+                Some(e.to_string())
+            }
+        };
+        
+        // 编辑已写入文件，旧的编辑日志与草稿都不再适用
+        self.invalidate_search();
+        self.draft_schedule.take(&source_path);
+        if let Some(draft_path) = self.draft_file_path(&source_path) {
+            let _draft_io = self.draft_schedule.io_lock();
+            if let Err(e) = Self::write_draft(&draft_path, None) {
+                println!("删除草稿失败: {}", e);
+            }
+        }
+        if guard.is_some() {
+            self.load_search_index(&target);
+        }
+        
+        Ok(SaveSummary {
+            path: target,
+            rows,
+            backup_path: backup_path.map(|backup_path| backup_path.to_string_lossy().into_owned()),
+            reopen_error,
+        })
+    }
+    
+    /// 文件被其他程序修改后，重新建立行索引并把编辑移植到文件的新内容上
+    ///
+    /// 按行号记录的字段修改移到新内容中内容相同的行上，找不到对应行时丢弃；
+    /// 批量操作按条件对新内容重新求值，增删行无法对应到新内容而被丢弃。
+    pub async fn rebase_edits<F>(&self, progress_callback: F) -> AppResult<RebaseSummary>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        if reader.journal().is_empty() {
+            return Err(AppError::General("没有需要移植的编辑".to_string()));
+        }
+        if Self::check_unchanged(reader).is_ok() {
+            return Err(AppError::General("文件未被修改，无需移植编辑".to_string()));
+        }
+        
+        let path = reader.path().to_string();
+        let mut rebased = JsonlReader::new(&path)?;
+        rebased.count_lines_with_progress(progress_callback)?;
+        let locations = Self::locate_edited_rows(reader, &rebased)?;
+        let (journal, dropped) = reader.journal().rebase(rebased.total_lines(), |line_num| locations.get(&line_num).copied(), |line_num| {
+            rebased.read_line_at(line_num).ok()
+                .and_then(|line| serde_json::from_str::<JsonValue>(&line).ok())
+        });
+        let kept = journal.len();
+        *rebased.journal_mut() = journal;
+        println!("移植编辑完成: 文件={}, 保留={}, 丢弃={}", path, kept, dropped);
+        
+        self.edits_changed(&rebased);
+        self.load_search_index(&path);
+        let status = Self::edit_status_of(&rebased);
+        *guard = Some(rebased);
+        
+        Ok(RebaseSummary {
+            kept_edits: kept,
+            dropped_edits: dropped,
+            status,
+        })
+    }
+    
+    /// 在文件的新内容中找出被单行编辑修改过的行的对应行（原行号 -> 新行号）
+    ///
+    /// 通过仍打开的文件句柄读取原内容并按内容哈希对齐：相同行号上内容未变时保留原行号，
+    /// 否则取新内容中唯一一个内容相同的行。原内容已被就地改写而无法读取时，这些修改都无法对应。
+    fn locate_edited_rows(reader: &JsonlReader, rebased: &JsonlReader) -> AppResult<HashMap<usize, usize>> {
+        let mut edited: Vec<usize> = reader.journal().ops().iter()
+            .filter_map(|op| match op {
+                EditOp::SetValue { row: RowId::Original(line_num), .. } => Some(*line_num),
+                _ => None,
+            })
+            .collect();
+        edited.sort_unstable();
+        edited.dedup();
+        if edited.is_empty() {
+            return Ok(HashMap::new());
+        }
+        
+        let base = match reader.base_file() {
+            Ok(base) => base,
+            Err(e) => {
+                println!("无法读取原内容，单行编辑将被丢弃: {}", e);
+                return Ok(HashMap::new());
+            }
+        };
+        let old_lines = MappedLines::from_file(&base, reader.line_offsets())?;
+        let old_hashes: Vec<(usize, u128)> = edited.iter()
+            .filter_map(|&line_num| old_lines.line(line_num).map(|line| (line_num, hash128(line))))
+            .collect();
+        let wanted: HashSet<u128> = old_hashes.iter().map(|&(_, hash)| hash).collect();
+        
+        // 新内容中与被编辑行内容相同的行，按行号升序
+        let new_lines = MappedLines::open(rebased.path(), rebased.line_offsets())?;
+        let mut matches: HashMap<u128, Vec<usize>> = HashMap::new();
+        for (line_num, hash) in new_lines.par_filter_map(|line_num, line| {
+            let hash = hash128(line);
+            wanted.contains(&hash).then_some((line_num, hash))
+        }) {
+            matches.entry(hash).or_default().push(line_num);
+        }
+        
+        // 行号不变的优先对应，其余行只在对应行唯一且未被占用时对应
+        let mut locations = HashMap::new();
+        let mut moved: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &(line_num, hash) in &old_hashes {
+            let candidates = matches.get(&hash).map(Vec::as_slice).unwrap_or(&[]);
+            if candidates.binary_search(&line_num).is_ok() {
+                locations.insert(line_num, line_num);
+            } else if let [new_line] = candidates {
+                moved.entry(*new_line).or_default().push(line_num);
+            }
+        }
+        let claimed: HashSet<usize> = locations.values().copied().collect();
+        for (new_line, old_lines) in moved {
+            if let [line_num] = old_lines[..] {
+                if !claimed.contains(&new_line) {
+                    locations.insert(line_num, new_line);
+                }
+            }
+        }
+        Ok(locations)
+    }
+    
+    /// 获取编辑历史
+    pub async fn edit_history(&self) -> AppResult<EditHistory> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        let journal = reader.journal();
+        let entry = |op: &EditOp, undone: bool| EditHistoryEntry {
+            kind: op.kind().to_string(),
+            description: op.describe(),
+            undone,
+        };
+        let entries = journal.ops().iter().map(|op| entry(op, false))
+            .chain(journal.redo_ops().map(|op| entry(op, true)))
+            .collect();
+        Ok(EditHistory {
+            entries,
+            status: Self::edit_status_of(reader),
+        })
+    }
+    
+    /// 撤销最近的一次编辑
+    pub async fn undo_edit(&self) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        // 排序状态下行排列不能变化
+        if reader.sort_status().is_some() && reader.journal().ops().last().is_some_and(EditOp::is_structural) {
+            return Err(AppError::General("排序状态下无法撤销增删行，请先取消排序".to_string()));
+        }
+        let op = reader.journal_mut().undo()
+            .ok_or_else(|| AppError::General("没有可以撤销的编辑".to_string()))?;
+        println!("撤销编辑: {}", op.describe());
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 重做最近撤销的一次编辑
+    pub async fn redo_edit(&self) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        if reader.sort_status().is_some() && reader.journal().redo_ops().next().is_some_and(EditOp::is_structural) {
+            return Err(AppError::General("排序状态下无法重做增删行，请先取消排序".to_string()));
+        }
+        let total_lines = reader.total_lines();
+        let op = reader.journal_mut().redo(total_lines)
+            .ok_or_else(|| AppError::General("没有可以重做的编辑".to_string()))?;
+        println!("重做编辑: {}", op.describe());
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 丢弃全部尚未保存的编辑
+    pub async fn discard_edits(&self) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        reader.journal_mut().clear();
+        self.edits_changed(reader);
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 获取分页数据
+    pub async fn load_page(&self, page: usize, page_size: usize, transform: Option<&str>) -> AppResult<PagedResponse> {
+        let guard = self.current_reader.lock().await;
+        let transform = Self::compile_transform(transform)?;
+        match &*guard {
+            Some(reader) => reader.load_page(page, page_size, transform.as_ref()),
+            None => Err(AppError::General("未打开JSONL文件".to_string())),
+        }
+    }
+    
+    /// 获取展开嵌套字段后的分页数据
+    ///
+    /// 没有变换程序时列名由文件的结构推断结果生成，翻页时保持不变；
+    /// 优先复用已缓存的推断结果（不论是否抽样），否则抽样推断。
+    /// 有变换程序时列名由当前页的数据生成。
+    pub async fn load_flat_page(&self, page: usize, page_size: usize, transform: Option<&str>, options: &FlattenOptions) -> AppResult<PagedResponse> {
+        let mut columns = match transform.filter(|t| !t.trim().is_empty()) {
+            Some(_) => Vec::new(),
+            None => {
+                let cached = {
+                    let guard = self.current_reader.lock().await;
+                    let reader = guard.as_ref()
+                        .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+                    let fingerprint = FileFingerprint::of(reader.path())?;
+                    self.schema_cache.lock().ok().and_then(|mut cache| {
+                        cache.get(reader.path())
+                            .filter(|cached| cached.fingerprint == fingerprint)
+                            .map(|cached| cached.report.clone())
+                    })
+                };
+                let report = match cached {
+                    Some(report) => report,
+                    None => self.infer_schema(Some(FLAT_SCHEMA_SAMPLE), false, |_| {}).await?,
+                };
+                flat_columns(&report, options)
+            }
+        };
+        
+        let mut response = self.load_page(page, page_size, transform).await?;
+        response.data = response.data.into_iter().map(|row| flatten_row(row, options)).collect();
+        extend_columns(&mut columns, &response.data);
+        response.columns = Some(columns);
+        Ok(response)
+    }
+    
+    /// 搜索关键词
+    ///
+    /// 关键词与当前会话相同时复用已有结果，否则取消旧搜索并在后台启动新搜索。
+    /// 只要找到足够填满请求页的匹配就立即返回，扫描在后台继续进行。
+    pub async fn search<F>(
+        &self,
+        keyword: &str,
+        page: usize,
+        page_size: usize,
+        transform: Option<&str>,
+        progress_callback: F,
+    ) -> AppResult<PagedResponse>
+    where
+        F: Fn(SearchProgress) + Send + 'static,
+    {
+        let session = {
+            let guard = self.current_reader.lock().await;
+            let reader = guard.as_ref()
+                .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+            self.ensure_search_session(reader, keyword, progress_callback)?
+        };
+        
+        // 等待足够的匹配结果，期间不占用读取器
+        let start_idx = (page - 1) * page_size;
+        let progress = session.wait_for(start_idx + page_size).await;
+        
+        if progress.cancelled {
+            return Err(AppError::General("搜索已取消".to_string()));
+        }
+        
+        // 页码超出范围
+        if start_idx >= progress.matches && progress.matches > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        let lines = session.matches_range(start_idx, start_idx + page_size);
+        
+        let guard = self.current_reader.lock().await;
+        let transform = Self::compile_transform(transform)?;
+        match &*guard {
+            Some(reader) => {
+                let mut response = reader.load_rows(&lines, progress.matches, transform.as_ref())?;
+                response.complete = Some(progress.done);
+                response.matches = Some(response.data.iter()
+                    .map(|row| find_match_locations(row, keyword))
+                    .collect());
+                Ok(response)
+            }
+            None => Err(AppError::General("未打开JSONL文件".to_string())),
+        }
+    }
+    
+    /// 模糊搜索，结果按匹配质量排序
+    pub async fn fuzzy_search(
+        &self,
+        keyword: &str,
+        max_distance: Option<usize>,
+        page: usize,
+        page_size: usize,
+        transform: Option<&str>,
+    ) -> AppResult<PagedResponse> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        let transform = Self::compile_transform(transform)?;
+        
+        let results = self.fuzzy_results(reader, keyword, max_distance)?;
+        
+        // 计算分页
+        let total_matches = results.len();
+        let start_idx = (page - 1) * page_size;
+        let end_idx = std::cmp::min(start_idx + page_size, total_matches);
+        
+        // 页码超出范围
+        if start_idx >= total_matches && total_matches > 0 {
+            return Err(AppError::PageOutOfRange);
+        }
+        
+        let page_results = results.get(start_idx..end_idx).unwrap_or(&[]);
+        let lines: Vec<usize> = page_results.iter().map(|(line, _)| *line).collect();
+        
+        let mut response = reader.load_rows(&lines, total_matches, transform.as_ref())?;
+        response.scores = Some(response.lines.iter()
+            .filter_map(|line| page_results.iter().find(|(l, _)| l == line).map(|(_, score)| *score))
+            .collect());
+        
+        Ok(response)
+    }
+    
+    /// 获取模糊搜索结果，查询不变时复用缓存
+    fn fuzzy_results(&self, reader: &JsonlReader, keyword: &str, max_distance: Option<usize>) -> AppResult<Arc<Vec<(usize, f32)>>> {
+        let mut cache = self.fuzzy_cache.lock().map_err(|_| {
+            AppError::General("获取模糊搜索缓存锁失败".to_string())
+        })?;
+        
+        if let Some(cached) = cache.as_ref() {
+            if cached.path == reader.path() && cached.keyword == keyword && cached.max_distance == max_distance {
+                return Ok(cached.results.clone());
+            }
+        }
+        
+        let matcher = FuzzyMatcher::new(keyword, max_distance)?;
+        let start_time = Instant::now();
+        // 有尚未保存的编辑时在编辑后的数据中搜索
+        let results = if reader.journal().is_empty() {
+            fuzzy_scan(reader.path(), reader.line_offsets(), &matcher)?
+        } else {
+            Self::edited_fuzzy_matches(reader, &matcher)?
+        };
+        let results = Arc::new(results);
+        println!("模糊搜索完成: 关键词='{}', 匹配行数={}, 耗时={:?}", keyword, results.len(), start_time.elapsed());
+        
+        *cache = Some(FuzzyCache {
+            path: reader.path().to_string(),
+            keyword: keyword.to_string(),
+            max_distance,
+            results: results.clone(),
+        });
+        
+        Ok(results)
+    }
+    
+    /// 获取可复用的搜索会话，必要时启动新会话
+    fn ensure_search_session<F>(&self, reader: &JsonlReader, keyword: &str, progress_callback: F) -> AppResult<Arc<SearchSession>>
+    where
+        F: Fn(SearchProgress) + Send + 'static,
+    {
+        let mut guard = self.search_session.lock().map_err(|_| {
+            AppError::General("获取搜索会话锁失败".to_string())
+        })?;
+        
+        if let Some(session) = guard.as_ref() {
+            if session.keyword() == keyword && session.path() == reader.path() && !session.is_cancelled() {
+                return Ok(session.clone());
+            }
+            // 新的查询替换旧查询
+            session.cancel();
+        }
+        
+        let id = self.next_search_id.fetch_add(1, Ordering::Relaxed);
+        
+        // 有尚未保存的编辑时在编辑后的数据中搜索，否则有可用索引时直接由索引得出全部匹配
+        let session = if !reader.journal().is_empty() {
+            let lines = Self::edited_matches(reader, keyword)?;
+            println!("在编辑后的数据中搜索 {}: 关键词='{}', 匹配行数={}", id, keyword, lines.len());
+            let session = SearchSession::completed(id, reader.path(), keyword, lines);
+            progress_callback(session.progress());
+            session
+        } else {
+            match self.query_search_index(reader, keyword)? {
+                Some(lines) => {
+                    println!("使用搜索索引 {}: 关键词='{}', 匹配行数={}", id, keyword, lines.len());
+                    let session = SearchSession::completed(id, reader.path(), keyword, lines);
+                    progress_callback(session.progress());
+                    session
+                }
+                None => {
+                    println!("启动搜索会话 {}: 关键词='{}'", id, keyword);
+                    SearchSession::start(id, reader.path(), keyword, progress_callback)?
+                }
+            }
+        };
+        *guard = Some(session.clone());
+        
+        Ok(session)
+    }
+    
+    /// 在应用编辑后的数据中查找包含关键词的行，未编辑的行直接匹配原始内容
+    ///
+    /// 存在增删行时按编辑后的行排列扫描，返回的是当前的行位置。
+    fn edited_matches(reader: &JsonlReader, keyword: &str) -> AppResult<Vec<usize>> {
+        let lines = MappedLines::open(reader.path(), reader.line_offsets())?;
+        let keyword_lower = keyword.to_lowercase();
+        let journal = reader.journal();
+        // 无法解析为对象的行不受编辑影响，直接匹配原始内容
+        let original_matches = |line_num: usize, line: &str| {
+            match journal.is_edited(RowId::Original(line_num)).then(|| journal.edited_row(line_num, line)).flatten() {
+                Some(row) => line_matches(&row.to_string(), &keyword_lower),
+                None => line_matches(line, &keyword_lower),
+            }
+        };
+        
+        let Some(order) = journal.order() else {
+            return Ok(lines.par_filter_map(|line_num, line| original_matches(line_num, line).then_some(line_num)));
+        };
+        Ok(order
+            .par_iter()
+            .enumerate()
+            .filter_map(|(position, &row_id)| {
+                let matched = match row_id {
+                    RowId::Original(line_num) => lines.line(line_num).is_some_and(|line| original_matches(line_num, line)),
+                    RowId::Inserted(_) => reader.edited_row_at(position)
+                        .is_ok_and(|row| line_matches(&row.to_string(), &keyword_lower)),
+                };
+                matched.then_some(position)
+            })
+            .collect())
+    }
+    
+    /// 在应用编辑后的数据中进行模糊匹配，返回按得分排序的 `(行位置, 得分)`
+    ///
+    /// 与 `edited_matches` 相同，存在增删行时按编辑后的行排列扫描。
+    fn edited_fuzzy_matches(reader: &JsonlReader, matcher: &FuzzyMatcher) -> AppResult<Vec<(usize, f32)>> {
+        let lines = MappedLines::open(reader.path(), reader.line_offsets())?;
+        let journal = reader.journal();
+        let original_score = |line_num: usize, line: &str| {
+            match journal.is_edited(RowId::Original(line_num)).then(|| journal.edited_row(line_num, line)).flatten() {
+                Some(row) => matcher.score_line(&row.to_string()),
+                None => matcher.score_line(line),
+            }
+        };
+        
+        let mut results = match journal.order() {
+            None => lines.par_filter_map(|line_num, line| original_score(line_num, line).map(|score| (line_num, score))),
+            Some(order) => order
+                .par_iter()
+                .enumerate()
+                .filter_map(|(position, &row_id)| {
+                    let score = match row_id {
+                        RowId::Original(line_num) => lines.line(line_num).and_then(|line| original_score(line_num, line)),
+                        RowId::Inserted(_) => reader.edited_row_at(position)
+                            .ok()
+                            .and_then(|row| matcher.score_line(&row.to_string())),
+                    };
+                    score.map(|score| (position, score))
+                })
+                .collect(),
+        };
+        sort_by_score(&mut results);
+        Ok(results)
+    }
+    
+    /// 编辑日志变化后丢弃基于旧内容的搜索结果，并安排更新草稿
+    fn edits_changed(&self, reader: &JsonlReader) {
+        self.invalidate_search();
+        self.draft_schedule.schedule(reader.path());
+    }
+    
+    /// 草稿文件路径，未设置草稿目录时返回 `None`
+    fn draft_file_path(&self, source: &str) -> Option<PathBuf> {
+        Self::draft_path_in(&self.draft_dir, source)
+    }
+    
+    /// 草稿目录下某个源文件的草稿路径
+    fn draft_path_in(draft_dir: &Mutex<Option<PathBuf>>, source: &str) -> Option<PathBuf> {
+        let guard = draft_dir.lock().ok()?;
+        guard.as_ref().map(|dir| EditDraft::draft_path(dir, source))
+    }
+    
+    /// 启动后台线程，连续编辑停止一段时间后把编辑日志写入草稿
+    fn spawn_draft_writer(&self) {
+        let current_reader = self.current_reader.clone();
+        let draft_dir = self.draft_dir.clone();
+        let schedule = self.draft_schedule.clone();
+        std::thread::spawn(move || {
+            while let Some(source) = schedule.wait_due() {
+                let guard = current_reader.blocking_lock();
+                // 切换文件前已写入旧文件的草稿
+                let Some(reader) = guard.as_ref().filter(|reader| reader.path() == source) else {
+                    continue;
+                };
+                let Some(draft_path) = Self::draft_path_in(&draft_dir, &source) else {
+                    continue;
+                };
+                let draft = Self::draft_of(reader);
+                // 取得草稿文件锁后即释放读取器，写入期间不阻塞编辑
+                let _draft_io = schedule.io_lock();
+                drop(guard);
+                if let Err(e) = draft.and_then(|draft| Self::write_draft(&draft_path, draft)) {
+                    println!("保存草稿失败: {}", e);
+                }
+            }
+        });
+    }
+    
+    /// 立即写入当前文件尚未写入的草稿
+    fn flush_draft(&self, reader: &JsonlReader) {
+        if self.draft_schedule.take(reader.path()) {
+            if let Err(e) = self.persist_draft(reader) {
+                println!("保存草稿失败: {}", e);
+            }
+        }
+    }
+    
+    /// 把编辑日志写入草稿，没有编辑时删除草稿
+    fn persist_draft(&self, reader: &JsonlReader) -> AppResult<()> {
+        let Some(draft_path) = self.draft_file_path(reader.path()) else {
+            return Ok(());
+        };
+        let draft = Self::draft_of(reader)?;
+        let _draft_io = self.draft_schedule.io_lock();
+        Self::write_draft(&draft_path, draft)
+    }
+    
+    /// 记录读取器当前的编辑日志，没有编辑时返回 `None`
+    fn draft_of(reader: &JsonlReader) -> AppResult<Option<EditDraft>> {
+        if reader.journal().is_empty() {
+            return Ok(None);
+        }
+        let fingerprint = match reader.fingerprint() {
+            Some(fingerprint) => fingerprint,
+            None => FileFingerprint::of(reader.path())?,
+        };
+        Ok(Some(EditDraft::new(reader.path(), fingerprint, reader.journal())))
+    }
+    
+    /// 写入草稿，`draft` 为空时删除草稿
+    fn write_draft(draft_path: &Path, draft: Option<EditDraft>) -> AppResult<()> {
+        match draft {
+            Some(draft) => draft.save(draft_path),
+            None => {
+                if draft_path.exists() {
+                    std::fs::remove_file(draft_path)?;
+                }
+                Ok(())
+            }
+        }
+    }
+    
+    /// 读取当前文件的草稿，没有草稿时返回 `None`
+    fn load_draft(&self, reader: &JsonlReader) -> AppResult<Option<EditDraft>> {
+        let Some(draft_path) = self.draft_file_path(reader.path()) else {
+            return Ok(None);
+        };
+        if !draft_path.exists() {
+            return Ok(None);
+        }
+        let draft = EditDraft::load(&draft_path)?;
+        // 路径哈希冲突时视为没有草稿
+        Ok(Some(draft).filter(|draft| draft.source() == reader.path()))
+    }
+    
+    /// 草稿是否仍与当前文件一致：行索引与磁盘上的文件都必须与草稿基于的版本相同
+    fn draft_matches(reader: &JsonlReader, draft: &EditDraft) -> bool {
+        reader.fingerprint() == Some(draft.fingerprint())
+            && FileFingerprint::of(reader.path()).is_ok_and(|fingerprint| fingerprint == draft.fingerprint())
+    }
+    
+    /// 获取当前文件的草稿状态，没有草稿时返回 `None`
+    pub async fn draft_status(&self) -> AppResult<Option<DraftStatus>> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        self.flush_draft(reader);
+        Ok(self.load_draft(reader)?.map(|draft| DraftStatus {
+            saved_at_ms: draft.saved_at_ms(),
+            pending_edits: draft.pending_edits(),
+            file_changed: !Self::draft_matches(reader, &draft),
+        }))
+    }
+    
+    /// 恢复当前文件的草稿，文件在草稿保存后被修改时拒绝恢复
+    pub async fn restore_draft(&self) -> AppResult<EditStatus> {
+        let mut guard = self.current_reader.lock().await;
+        let reader = guard.as_mut()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        if !reader.journal().is_empty() {
+            return Err(AppError::General("当前已有尚未保存的编辑，请先保存或丢弃".to_string()));
+        }
+        
+        self.flush_draft(reader);
+        let draft = self.load_draft(reader)?
+            .ok_or_else(|| AppError::General("当前文件没有草稿".to_string()))?;
+        if !Self::draft_matches(reader, &draft) {
+            return Err(AppError::General("文件在草稿保存后已被修改，无法恢复草稿".to_string()));
+        }
+        
+        let journal = draft.into_journal(reader.total_lines());
+        if journal.has_row_changes() {
+            Self::check_row_changes_allowed(reader)?;
+        }
+        println!("恢复草稿: 文件={}, 操作数={}", reader.path(), journal.len());
+        *reader.journal_mut() = journal;
+        self.invalidate_search();
+        Ok(Self::edit_status_of(reader))
+    }
+    
+    /// 删除当前文件的草稿，返回是否存在草稿
+    pub async fn discard_draft(&self) -> AppResult<bool> {
+        let guard = self.current_reader.lock().await;
+        let reader = guard.as_ref()
+            .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+        
+        self.draft_schedule.take(reader.path());
+        let _draft_io = self.draft_schedule.io_lock();
+        match self.draft_file_path(reader.path()).filter(|draft_path| draft_path.exists()) {
+            Some(draft_path) => {
+                std::fs::remove_file(&draft_path)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+    
+    /// 编辑后丢弃基于旧内容的搜索结果
+    fn invalidate_search(&self) {
+        self.cancel_search();
+        if let Ok(mut cache) = self.fuzzy_cache.lock() {
+            *cache = None;
+        }
+    }
+    
+    /// 使用搜索索引求出匹配行，索引不可用时返回 `None`
+    fn query_search_index(&self, reader: &JsonlReader, keyword: &str) -> AppResult<Option<Vec<usize>>> {
+        let Some(index) = self.current_search_index(reader.path()) else {
+            return Ok(None);
+        };
+        
+        // 文件已被修改，索引失效
+        if !index.is_fresh() {
+            println!("搜索索引已过期，删除: {}", index.source());
+            self.drop_search_index();
+            return Ok(None);
+        }
+        
+        let Some(candidates) = index.candidates(keyword) else {
+            return Ok(None);
+        };
+        
+        // 索引只用于缩小范围，候选行按与全文扫描相同的规则校验
+        let keyword_lower = keyword.to_lowercase();
+        let lines = MappedLines::open(reader.path(), reader.line_offsets())?;
+        Ok(Some(lines.par_filter_map_lines(&candidates, |line_num, line| {
+            line_matches(line, &keyword_lower).then_some(line_num)
+        })))
+    }
+    
+    /// 获取属于指定文件的搜索索引
+    fn current_search_index(&self, path: &str) -> Option<Arc<InvertedIndex>> {
+        let guard = self.search_index.lock().ok()?;
+        guard.as_ref()
+            .filter(|index| index.source() == path)
+            .cloned()
+    }
+    
+    /// 当前文件对应的索引文件路径
+    fn index_file_path(&self, source: &str) -> Option<PathBuf> {
+        let guard = self.index_dir.lock().ok()?;
+        guard.as_ref().map(|dir| InvertedIndex::index_path(dir, source))
+    }
+    
+    /// 打开文件时尝试加载磁盘上的索引
+    fn load_search_index(&self, path: &str) {
+        // 新文件打开后，旧文件的索引构建没有意义
+        if let Ok(mut guard) = self.index_build.lock() {
+            if let Some(cancelled) = guard.take() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+        
+        let index = self.index_file_path(path)
+            .filter(|index_path| index_path.exists())
+            .and_then(|index_path| match InvertedIndex::load(&index_path) {
+                Ok(index) if index.source() == path && index.is_fresh() => Some(Arc::new(index)),
+                Ok(_) => {
+                    println!("搜索索引已过期，删除: {}", index_path.display());
+                    let _ = std::fs::remove_file(&index_path);
+                    None
+                }
+                Err(e) => {
+                    println!("加载搜索索引失败: {}", e);
+                    None
+                }
+            });
+        
+        if index.is_some() {
+            println!("已加载搜索索引: {}", path);
+        }
+        
+        if let Ok(mut guard) = self.search_index.lock() {
+            *guard = index;
+        }
+    }
+    
+    /// 在后台为当前文件构建搜索索引
+    pub async fn build_search_index<F>(&self, progress_callback: F) -> AppResult<()>
+    where
+        F: Fn(LoadingProgress) + Send + 'static,
+    {
+        let path = {
+            let guard = self.current_reader.lock().await;
+            let reader = guard.as_ref()
+                .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+            reader.path().to_string()
+        };
+        
+        let index_path = self.index_file_path(&path)
+            .ok_or_else(|| AppError::General("未设置索引目录".to_string()))?;
+        
+        // 取消正在进行的构建
+        let cancelled = Arc::new(AtomicBool::new(false));
+        {
+            let mut guard = self.index_build.lock().map_err(|_| {
+                AppError::General("获取索引锁失败".to_string())
+            })?;
+            if let Some(previous) = guard.replace(cancelled.clone()) {
+                previous.store(true, Ordering::Relaxed);
+            }
+        }
+        
+        let search_index = self.search_index.clone();
+        let index_build = self.index_build.clone();
+        std::thread::spawn(move || {
+            let result = InvertedIndex::build(&path, &cancelled, &progress_callback)
+                .and_then(|index| {
+                    progress_callback(LoadingProgress {
+                        current: index.total_lines(),
+                        total: index.total_lines(),
+                        stage: "保存搜索索引".to_string(),
+                        percentage: 100.0,
+                    });
+                    index.save(&index_path)?;
+                    Ok(index)
+                });
+            
+            match result {
+                Ok(index) if !cancelled.load(Ordering::Relaxed) => {
+                    let total_lines = index.total_lines();
+                    if let Ok(mut guard) = search_index.lock() {
+                        *guard = Some(Arc::new(index));
+                    }
+                    progress_callback(LoadingProgress {
+                        current: total_lines,
+                        total: total_lines,
+                        stage: "搜索索引构建完成".to_string(),
+                        percentage: 100.0,
+                    });
+                }
+                Ok(_) => {
+                    println!("索引构建已取消: {}", path);
+                }
+                Err(e) => {
+                    println!("索引构建失败: {}", e);
+                    progress_callback(LoadingProgress {
+                        current: 0,
+                        total: 0,
+                        stage: format!("搜索索引构建失败: {}", e),
+                        percentage: 0.0,
+                    });
+                }
+            }
+            
+            // 清除自己的构建标记
+            if let Ok(mut guard) = index_build.lock() {
+                if guard.as_ref().is_some_and(|flag| Arc::ptr_eq(flag, &cancelled)) {
+                    *guard = None;
+                }
+            }
+        });
+        
+        Ok(())
+    }
+    
+    /// 获取当前文件的索引状态
+    pub async fn index_status(&self) -> AppResult<IndexStatus> {
+        let path = {
+            let guard = self.current_reader.lock().await;
+            let reader = guard.as_ref()
+                .ok_or_else(|| AppError::General("未打开JSONL文件".to_string()))?;
+            reader.path().to_string()
+        };
+        
+        let index = self.current_search_index(&path);
+        let building = self.index_build.lock()
+            .map(|guard| guard.is_some())
+            .unwrap_or(false);
+        
+        Ok(IndexStatus {
+            ready: index.is_some(),
+            building,
+            index_path: self.index_file_path(&path).map(|p| p.display().to_string()),
+            total_lines: index.as_ref().map(|index| index.total_lines()).unwrap_or(0),
+            token_count: index.as_ref().map(|index| index.token_count()).unwrap_or(0),
+        })
+    }
+    
+    /// 删除当前的搜索索引（内存与磁盘），返回是否存在索引
+    pub fn drop_search_index(&self) -> bool {
+        let index = self.search_index.lock()
+            .ok()
+            .and_then(|mut guard| guard.take());
+        
+        match index {
+            Some(index) => {
+                if let Some(index_path) = self.index_file_path(index.source()) {
+                    let _ = std::fs::remove_file(index_path);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// 取消当前搜索，返回是否有搜索被取消
+    pub fn cancel_search(&self) -> bool {
+        let Ok(mut guard) = self.search_session.lock() else {
+            return false;
+        };
+        
+        match guard.take() {
+            Some(session) => {
+                let running = !session.progress().done;
+                session.cancel();
+                running
+            }
+            None => false,
+        }
+    }
+    
+    /// 导出当前文件（可过滤、可变换）
+    pub async fn export(&self, output: &str, keyword: Option<&str>, transform: Option<&str>) -> AppResult<ExportSummary> {
+        let guard = self.current_reader.lock().await;
+        let transform = Self::compile_transform(transform)?;
+        match &*guard {
+            Some(reader) => reader.export(output, keyword, transform.as_ref()),
+            None => Err(AppError::General("未打开JSONL文件".to_string())),
+        }
+    }
+    
+    /// 编译可选的变换程序，空字符串视为不变换
+    fn compile_transform(transform: Option<&str>) -> AppResult<Option<RowTransform>> {
+        match transform {
+            Some(program) if !program.trim().is_empty() => RowTransform::compile(program).map(Some),
+            _ => Ok(None),
+        }
+    }
+    
     /// 获取当前文件的总行数
     pub async fn total_lines(&self) -> AppResult<usize> {
         let guard = self.current_reader.lock().await;
         match &*guard {
-            Some(reader) => Ok(reader.total_lines()),
+            Some(reader) => Ok(reader.row_count()),
             None => Err(AppError::General("未打开JSONL文件".to_string())),
         }
     }
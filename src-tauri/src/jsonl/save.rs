@@ -0,0 +1,211 @@
+use crate::jsonl::{EditJournal, MappedLines, RowId};
+use crate::models::{AppError, AppResult, JsonValue, LoadingProgress};
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 每批并行生成的行数
+const BATCH_ROWS: usize = 50_000;
+
+/// 写入缓冲区大小
+const WRITE_BUFFER_SIZE: usize = 1 << 20;
+
+/// 已写入临时文件、尚未替换目标文件的保存结果
+///
+/// 临时文件位于目标文件所在目录，保证最终的重命名是原子操作；
+/// 未提交就被丢弃时删除临时文件，目标文件保持不变。
+pub struct StagedSave {
+    /// 临时文件路径
+    temp_path: PathBuf,
+
+    /// 目标文件路径
+    target: PathBuf,
+
+    /// 写入的行数
+    rows: usize,
+
+    /// 是否已替换目标文件
+    committed: bool,
+}
+
+impl StagedSave {
+    /// 把原文件与编辑日志合并写入目标文件旁的临时文件，并同步到磁盘
+    ///
+    /// `source` 为建立行索引时的文件内容。未被修改的行按原始字节写出；
+    /// 批量操作没有改变的行同样保留原样，不会因重新序列化而改变字段顺序或空白。
+    pub fn write<F>(
+        source: &File,
+        line_offsets: &[u64],
+        journal: &EditJournal,
+        target: &Path,
+        progress_callback: F,
+    ) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let file_name = target
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| AppError::General("无效的文件路径".to_string()))?;
+        let temp_path = target.with_file_name(format!(".{}.smart-slice-{}.tmp", file_name, std::process::id()));
+
+        let mapped = MappedLines::from_file(source, line_offsets)?;
+        let total_lines = line_offsets.len();
+        let row_count = journal.row_count(total_lines);
+
+        let file = File::create(&temp_path)?;
+        // 此后出错时由 Drop 删除临时文件
+        let mut staged = Self {
+            temp_path,
+            target: target.to_path_buf(),
+            rows: 0,
+            committed: false,
+        };
+        let mut writer = BufWriter::with_capacity(WRITE_BUFFER_SIZE, file);
+
+        let progress_interval = Duration::from_millis(100);
+        let mut last_progress_time = Instant::now();
+        progress_callback(LoadingProgress {
+            current: 0,
+            total: row_count,
+            stage: "写入编辑后的文件".to_string(),
+            percentage: 0.0,
+        });
+
+        for start in (0..row_count).step_by(BATCH_ROWS) {
+            let end = (start + BATCH_ROWS).min(row_count);
+            let batch = (start..end)
+                .into_par_iter()
+                .map(|position| {
+                    let row_id = journal
+                        .row_at(position, total_lines)
+                        .ok_or_else(|| AppError::General(format!("行排列中缺少第 {} 行", position)))?;
+                    row_bytes(&mapped, journal, row_id)
+                })
+                .collect::<AppResult<Vec<_>>>()?;
+
+            for bytes in batch {
+                writer.write_all(&bytes)?;
+                if !bytes.ends_with(b"\n") {
+                    writer.write_all(b"\n")?;
+                }
+            }
+            staged.rows = end;
+
+            let now = Instant::now();
+            if now.duration_since(last_progress_time) >= progress_interval {
+                last_progress_time = now;
+                progress_callback(LoadingProgress {
+                    current: end,
+                    total: row_count,
+                    stage: "写入编辑后的文件".to_string(),
+                    percentage: end as f32 / row_count as f32 * 100.0,
+                });
+            }
+        }
+
+        let file = writer.into_inner().map_err(|e| AppError::Io(e.into_error()))?;
+        progress_callback(LoadingProgress {
+            current: row_count,
+            total: row_count,
+            stage: "同步到磁盘".to_string(),
+            percentage: 100.0,
+        });
+        file.sync_all()?;
+
+        // 保留目标文件原有的权限
+        if let Ok(metadata) = std::fs::metadata(target) {
+            std::fs::set_permissions(&staged.temp_path, metadata.permissions())?;
+        }
+
+        Ok(staged)
+    }
+
+    /// 写入的行数
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// 用临时文件原子地替换目标文件，`backup` 为真时先把原文件保留为 `.bak`
+    ///
+    /// 返回备份文件路径。替换前目标文件的句柄应已关闭（Windows 下无法替换打开中的文件）。
+    pub fn commit(mut self, backup: bool) -> AppResult<Option<PathBuf>> {
+        let backup_path = if backup && self.target.exists() {
+            let mut name = self.target.as_os_str().to_owned();
+            name.push(".bak");
+            let backup_path = PathBuf::from(name);
+            if backup_path.exists() {
+                std::fs::remove_file(&backup_path)?;
+            }
+            // 优先使用硬链接，避免复制整个文件；不支持时退回复制
+            if std::fs::hard_link(&self.target, &backup_path).is_err() {
+                std::fs::copy(&self.target, &backup_path)?;
+            }
+            Some(backup_path)
+        } else {
+            None
+        };
+
+        std::fs::rename(&self.temp_path, &self.target)?;
+        self.committed = true;
+
+        // 同步目录项，确保重命名在断电后仍然有效
+        #[cfg(unix)]
+        if let Some(dir) = self.target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            if let Ok(dir) = File::open(dir) {
+                let _ = dir.sync_all();
+            }
+        }
+
+        Ok(backup_path)
+    }
+}
+
+impl Drop for StagedSave {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// 生成一行要写入的内容，未被修改的原文件行直接借用原始字节
+pub(crate) fn row_bytes<'a>(mapped: &'a MappedLines<'_>, journal: &EditJournal, row_id: RowId) -> AppResult<Cow<'a, [u8]>> {
+    let serialize = |row: &JsonValue| {
+        serde_json::to_vec(row)
+            .map(Cow::Owned)
+            .map_err(|e| AppError::General(format!("序列化失败: {}", e)))
+    };
+
+    match row_id {
+        RowId::Original(line_num) => {
+            let raw = mapped.raw_line(line_num).unwrap_or_default();
+            if !journal.is_edited(row_id) {
+                return Ok(Cow::Borrowed(raw));
+            }
+            // 无法解析为对象的行不受编辑影响
+            let Some(original) = std::str::from_utf8(raw)
+                .ok()
+                .and_then(|line| serde_json::from_str::<JsonValue>(line.trim()).ok())
+                .filter(JsonValue::is_object)
+            else {
+                return Ok(Cow::Borrowed(raw));
+            };
+            let mut row = original.clone();
+            journal.apply(row_id, &mut row)?;
+            if row == original {
+                Ok(Cow::Borrowed(raw))
+            } else {
+                serialize(&row)
+            }
+        }
+        RowId::Inserted(index) => {
+            let mut row = journal.inserted_content(index).cloned().unwrap_or_default();
+            journal.apply(row_id, &mut row)?;
+            serialize(&row)
+        }
+    }
+}
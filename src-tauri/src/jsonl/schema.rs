@@ -0,0 +1,606 @@
+use crate::jsonl::{JsonPath, MappedLines, PathSegment};
+use crate::models::{AppResult, FieldSchema, FieldType, JsonValue, LoadingProgress, SchemaReport};
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// 每批并行处理的行数，每批结束后报告一次进度
+const BATCH_LINES: usize = 50_000;
+
+/// 每个字段保留的示例值数量
+const MAX_EXAMPLES: usize = 3;
+
+/// 字符串示例值的最大字符数
+const MAX_EXAMPLE_CHARS: usize = 80;
+
+/// 每个字段最多记录的不同字符串值数量，用于识别枚举
+const MAX_DISTINCT_STRINGS: usize = 50;
+
+/// 可作为枚举值的字符串最大字符数
+const MAX_ENUM_VALUE_CHARS: usize = 100;
+
+/// JSON 值的类型名
+pub fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+/// 在路径字符串后追加一个字段名
+///
+/// 数组元素统一记作 `[]`，因此推断出的路径如 `messages[].content` 覆盖所有下标。
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    if key.is_empty() || key.contains(['.', '[', ']', '"']) {
+        path.push_str(&JsonPath::root().child_key(key).to_string());
+    } else {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+    }
+}
+
+/// 递归访问一个值及其所有子字段，`visit` 收到每个值与其路径
+///
+/// `path` 为 `value` 自身的路径，子字段的路径按 `push_key` 追加，访问结束后恢复原样。
+pub(crate) fn walk_paths<F>(value: &JsonValue, path: &mut String, visit: &mut F)
+where
+    F: FnMut(&str, &JsonValue),
+{
+    visit(path, value);
+
+    let len = path.len();
+    match value {
+        JsonValue::Object(map) => {
+            for (key, item) in map {
+                push_key(path, key);
+                walk_paths(item, path, visit);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Array(items) => {
+            path.push_str("[]");
+            for item in items {
+                walk_paths(item, path, visit);
+            }
+            path.truncate(len);
+        }
+        _ => {}
+    }
+}
+
+/// 单个字段路径的统计
+struct FieldStats {
+    /// 各类型出现次数
+    types: HashMap<&'static str, usize>,
+
+    /// 出现次数（数组内的字段每个元素计一次）
+    occurrences: usize,
+
+    /// 含有该字段的行数
+    present_rows: usize,
+
+    /// 值为 null 的次数
+    nulls: usize,
+
+    /// 首次出现的行号
+    first_line: usize,
+
+    /// 最近一次计入 `present_rows` 的行号
+    last_line: Option<usize>,
+
+    /// 示例值
+    examples: Vec<JsonValue>,
+
+    /// 不同的字符串值，超过上限后为 `None`
+    strings: Option<BTreeSet<String>>,
+
+    /// 数组的最大长度，没有出现过数组时为 `None`
+    max_items: Option<usize>,
+}
+
+impl Default for FieldStats {
+    fn default() -> Self {
+        Self {
+            types: HashMap::new(),
+            occurrences: 0,
+            present_rows: 0,
+            nulls: 0,
+            first_line: 0,
+            last_line: None,
+            examples: Vec::new(),
+            strings: Some(BTreeSet::new()),
+            max_items: None,
+        }
+    }
+}
+
+impl FieldStats {
+    /// 记录一次出现
+    fn record(&mut self, value: &JsonValue, line_num: usize) {
+        *self.types.entry(type_name(value)).or_insert(0) += 1;
+        self.occurrences += 1;
+        if value.is_null() {
+            self.nulls += 1;
+        }
+        if self.last_line != Some(line_num) {
+            if self.last_line.is_none() {
+                self.first_line = line_num;
+            }
+            self.last_line = Some(line_num);
+            self.present_rows += 1;
+        }
+        if let JsonValue::Array(items) = value {
+            self.max_items = self.max_items.max(Some(items.len()));
+        }
+        if let JsonValue::String(text) = value {
+            if let Some(strings) = &mut self.strings {
+                if text.chars().count() > MAX_ENUM_VALUE_CHARS {
+                    self.strings = None;
+                } else if !strings.contains(text.as_str()) {
+                    strings.insert(text.clone());
+                    if strings.len() > MAX_DISTINCT_STRINGS {
+                        self.strings = None;
+                    }
+                }
+            }
+        }
+        if self.examples.len() < MAX_EXAMPLES {
+            let example = match value {
+                JsonValue::Object(_) | JsonValue::Array(_) | JsonValue::Null => return,
+                JsonValue::String(text) if text.chars().count() > MAX_EXAMPLE_CHARS => {
+                    JsonValue::String(format!("{}…", text.chars().take(MAX_EXAMPLE_CHARS).collect::<String>()))
+                }
+                other => other.clone(),
+            };
+            if !self.examples.contains(&example) {
+                self.examples.push(example);
+            }
+        }
+    }
+
+    /// 合并另一部分行的统计
+    fn merge(&mut self, other: FieldStats) {
+        for (name, count) in other.types {
+            *self.types.entry(name).or_insert(0) += count;
+        }
+        self.occurrences += other.occurrences;
+        self.nulls += other.nulls;
+        if self.present_rows == 0 || (other.present_rows > 0 && other.first_line < self.first_line) {
+            self.first_line = other.first_line;
+        }
+        self.present_rows += other.present_rows;
+        self.max_items = self.max_items.max(other.max_items);
+        self.strings = match (self.strings.take(), other.strings) {
+            (Some(mut strings), Some(other_strings)) => {
+                strings.extend(other_strings);
+                (strings.len() <= MAX_DISTINCT_STRINGS).then_some(strings)
+            }
+            _ => None,
+        };
+        for example in other.examples {
+            if self.examples.len() >= MAX_EXAMPLES {
+                break;
+            }
+            if !self.examples.contains(&example) {
+                self.examples.push(example);
+            }
+        }
+    }
+}
+
+/// 一部分行的推断结果，可以并行计算后合并
+#[derive(Default)]
+struct SchemaAccumulator {
+    /// 各字段路径的统计
+    fields: HashMap<String, FieldStats>,
+
+    /// 成功解析为对象的行数
+    rows: usize,
+
+    /// 无法解析或不是对象的行数
+    invalid_rows: usize,
+}
+
+impl SchemaAccumulator {
+    /// 记录一行
+    fn add_line(&mut self, line_num: usize, line: &str) {
+        match serde_json::from_str::<JsonValue>(line) {
+            Ok(JsonValue::Object(map)) => {
+                self.rows += 1;
+                let mut path = String::new();
+                for (key, value) in &map {
+                    push_key(&mut path, key);
+                    walk_paths(value, &mut path, &mut |path, value| self.record(path, value, line_num));
+                    path.clear();
+                }
+            }
+            _ => self.invalid_rows += 1,
+        }
+    }
+
+    /// 记录某个路径上的一个值
+    fn record(&mut self, path: &str, value: &JsonValue, line_num: usize) {
+        match self.fields.get_mut(path) {
+            Some(stats) => stats.record(value, line_num),
+            None => {
+                let mut stats = FieldStats::default();
+                stats.record(value, line_num);
+                self.fields.insert(path.to_string(), stats);
+            }
+        }
+    }
+
+    /// 合并另一部分行的结果
+    fn merge(mut self, other: SchemaAccumulator) -> Self {
+        self.rows += other.rows;
+        self.invalid_rows += other.invalid_rows;
+        for (path, stats) in other.fields {
+            match self.fields.get_mut(&path) {
+                Some(existing) => existing.merge(stats),
+                None => {
+                    self.fields.insert(path, stats);
+                }
+            }
+        }
+        self
+    }
+}
+
+/// 推断文件中所有行（或均匀抽样的行）的字段结构
+///
+/// `sample_size` 小于总行数时按固定间隔抽样。
+pub fn infer_schema<F>(path: &str, line_offsets: &[u64], sample_size: Option<usize>, progress_callback: F) -> AppResult<SchemaReport>
+where
+    F: Fn(LoadingProgress),
+{
+    let lines = MappedLines::open(path, line_offsets)?;
+    let total = lines.len();
+
+    let sample: Vec<usize> = match sample_size {
+        Some(size) if size < total => (0..size).map(|i| i * total / size).collect(),
+        _ => (0..total).collect(),
+    };
+
+    let mut result = SchemaAccumulator::default();
+    for (batch_index, batch) in sample.chunks(BATCH_LINES).enumerate() {
+        let partial = batch
+            .par_iter()
+            .fold(SchemaAccumulator::default, |mut acc, &line_num| {
+                if let Some(line) = lines.line(line_num).filter(|line| !line.is_empty()) {
+                    acc.add_line(line_num, line);
+                }
+                acc
+            })
+            .reduce(SchemaAccumulator::default, SchemaAccumulator::merge);
+        result = result.merge(partial);
+
+        let done = (batch_index * BATCH_LINES + batch.len()).min(sample.len());
+        progress_callback(LoadingProgress {
+            current: done,
+            total: sample.len(),
+            stage: "推断字段结构".to_string(),
+            percentage: done as f32 / sample.len() as f32 * 100.0,
+        });
+    }
+
+    let rows = result.rows;
+    let mut fields: Vec<(String, FieldStats)> = result.fields.into_iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // 顶层字段按出现行数降序、首次出现位置升序作为表格列
+    let mut top_level: Vec<(String, &FieldStats)> = fields
+        .iter()
+        .filter_map(|(path, stats)| match JsonPath::parse(path).ok()?.segments() {
+            [PathSegment::Key(key)] => Some((key.clone(), stats)),
+            _ => None,
+        })
+        .collect();
+    top_level.sort_by(|a, b| b.1.present_rows.cmp(&a.1.present_rows).then(a.1.first_line.cmp(&b.1.first_line)));
+    let columns = top_level.into_iter().map(|(key, _)| key).collect();
+
+    let fields = fields
+        .into_iter()
+        .map(|(path, stats)| {
+            let mut types: Vec<FieldType> = stats
+                .types
+                .iter()
+                .map(|(name, count)| FieldType {
+                    name: name.to_string(),
+                    count: *count,
+                })
+                .collect();
+            types.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+            // null 不构成联合类型，整数与小数视为同一类型
+            let kinds: BTreeSet<&str> = types
+                .iter()
+                .filter(|t| t.name != "null")
+                .map(|t| if t.name == "integer" { "number" } else { t.name.as_str() })
+                .collect();
+            let union = kinds.len() > 1;
+
+            FieldSchema {
+                path,
+                types,
+                union,
+                occurrences: stats.occurrences,
+                present_rows: stats.present_rows,
+                presence_ratio: if rows > 0 { stats.present_rows as f32 / rows as f32 } else { 0.0 },
+                null_ratio: if stats.occurrences > 0 { stats.nulls as f32 / stats.occurrences as f32 } else { 0.0 },
+                examples: stats.examples,
+                distinct_strings: stats
+                    .strings
+                    .filter(|strings| !strings.is_empty())
+                    .map(|strings| strings.into_iter().collect()),
+                max_items: stats.max_items,
+            }
+        })
+        .collect();
+
+    Ok(SchemaReport {
+        path: path.to_string(),
+        total_lines: total,
+        rows_scanned: rows,
+        invalid_rows: result.invalid_rows,
+        sampled: sample.len() < total,
+        sample_size,
+        columns,
+        fields,
+    })
+}
+
+/// 推断结构中路径的一段
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SchemaSegment {
+    /// 对象字段
+    Key(String),
+
+    /// 数组元素 `[]`
+    Items,
+}
+
+/// 解析推断结构中的路径，例如 `messages[].content`、`["a.b"].c`
+pub(crate) fn parse_schema_path(path: &str) -> Option<Vec<SchemaSegment>> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '.' => {
+                chars.next();
+            }
+            '[' => {
+                chars.next();
+                match chars.next()? {
+                    ']' => segments.push(SchemaSegment::Items),
+                    '"' => {
+                        let mut key = String::new();
+                        loop {
+                            match chars.next()? {
+                                '\\' => key.push(chars.next()?),
+                                '"' => break,
+                                ch => key.push(ch),
+                            }
+                        }
+                        if chars.next()? != ']' {
+                            return None;
+                        }
+                        segments.push(SchemaSegment::Key(key));
+                    }
+                    _ => return None,
+                }
+            }
+            _ => {
+                let mut key = String::new();
+                while let Some(&ch) = chars.peek() {
+                    if ch == '.' || ch == '[' {
+                        break;
+                    }
+                    key.push(ch);
+                    chars.next();
+                }
+                segments.push(SchemaSegment::Key(key));
+            }
+        }
+    }
+    Some(segments)
+}
+
+/// 导出 JSON Schema 的选项
+#[derive(Debug, Clone)]
+pub struct SchemaExportOptions {
+    /// 出现比例（相对于所在对象）不低于该值的字段列为必需字段
+    pub required_threshold: f32,
+
+    /// 不同值不超过该数量的字符串字段输出为枚举，0 表示不识别枚举
+    pub enum_max_values: usize,
+}
+
+impl Default for SchemaExportOptions {
+    fn default() -> Self {
+        Self {
+            required_threshold: 1.0,
+            enum_max_values: 10,
+        }
+    }
+}
+
+/// 推断结构中的一个节点
+#[derive(Default)]
+pub(crate) struct SchemaNode<'a> {
+    /// 该路径的统计，只作为中间层出现的路径为 `None`
+    pub(crate) field: Option<&'a FieldSchema>,
+
+    /// 子字段，按路径顺序
+    pub(crate) properties: Vec<(String, SchemaNode<'a>)>,
+
+    /// 数组元素
+    pub(crate) items: Option<Box<SchemaNode<'a>>>,
+}
+
+impl<'a> SchemaNode<'a> {
+    /// 由推断结果构建结构树，根节点对应整行
+    pub(crate) fn from_report(report: &'a SchemaReport) -> Self {
+        let mut root = SchemaNode::default();
+        for field in &report.fields {
+            if let Some(segments) = parse_schema_path(&field.path) {
+                root.child(&segments).field = Some(field);
+            }
+        }
+        root
+    }
+
+    /// 按路径找到或创建子节点
+    fn child(&mut self, segments: &[SchemaSegment]) -> &mut SchemaNode<'a> {
+        let Some((first, rest)) = segments.split_first() else {
+            return self;
+        };
+        let node = match first {
+            SchemaSegment::Items => self.items.get_or_insert_with(Default::default).as_mut(),
+            SchemaSegment::Key(key) => {
+                let index = match self.properties.iter().position(|(name, _)| name == key) {
+                    Some(index) => index,
+                    None => {
+                        self.properties.push((key.clone(), SchemaNode::default()));
+                        self.properties.len() - 1
+                    }
+                };
+                &mut self.properties[index].1
+            }
+        };
+        node.child(rest)
+    }
+
+    /// 生成该节点的 Schema
+    fn to_schema(&self, options: &SchemaExportOptions) -> JsonValue {
+        let mut schema = serde_json::Map::new();
+
+        let count_of = |name: &str| {
+            self.field
+                .and_then(|field| field.types.iter().find(|t| t.name == name))
+                .map(|t| t.count)
+                .unwrap_or(0)
+        };
+
+        if let Some(field) = self.field {
+            // 整数与小数同时出现时合并为 number
+            let has_number = count_of("number") > 0;
+            let mut types: Vec<&str> = field
+                .types
+                .iter()
+                .map(|t| t.name.as_str())
+                .filter(|name| !(has_number && *name == "integer"))
+                .collect();
+            types.sort();
+            match types.as_slice() {
+                [] => {}
+                [single] => {
+                    schema.insert("type".to_string(), JsonValue::from(*single));
+                }
+                _ => {
+                    schema.insert("type".to_string(), JsonValue::from(types.clone()));
+                }
+            }
+
+            // 低基数的字符串字段输出为枚举
+            let only_strings = types.iter().all(|name| *name == "string" || *name == "null") && types.contains(&"string");
+            if let Some(values) = field.distinct_strings.as_ref().filter(|_| only_strings) {
+                let string_count = count_of("string");
+                if options.enum_max_values > 0 && values.len() <= options.enum_max_values && string_count >= values.len() * 2 {
+                    let mut values: Vec<JsonValue> = values.iter().map(|v| JsonValue::from(v.as_str())).collect();
+                    if types.contains(&"null") {
+                        values.push(JsonValue::Null);
+                    }
+                    schema.insert("enum".to_string(), JsonValue::Array(values));
+                }
+            }
+        }
+
+        let object_count = if self.field.is_some() { count_of("object") } else { 0 };
+        if !self.properties.is_empty() {
+            let mut properties = serde_json::Map::new();
+            let mut required = Vec::new();
+            for (name, child) in &self.properties {
+                properties.insert(name.clone(), child.to_schema(options));
+                let occurrences = child.field.map(|field| field.occurrences).unwrap_or(0);
+                if object_count > 0 && occurrences as f32 / object_count as f32 >= options.required_threshold {
+                    required.push(JsonValue::from(name.as_str()));
+                }
+            }
+            schema.insert("properties".to_string(), JsonValue::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), JsonValue::Array(required));
+            }
+        }
+
+        if let Some(items) = &self.items {
+            schema.insert("items".to_string(), items.to_schema(options));
+        }
+
+        JsonValue::Object(schema)
+    }
+}
+
+/// 将推断出的结构转换为 JSON Schema（2020-12）文档
+pub fn schema_document(report: &SchemaReport, options: &SchemaExportOptions) -> JsonValue {
+    let root = SchemaNode::from_report(report);
+
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    for (name, child) in &root.properties {
+        properties.insert(name.clone(), child.to_schema(options));
+        if child.field.is_some_and(|field| field.presence_ratio >= options.required_threshold) {
+            required.push(JsonValue::from(name.as_str()));
+        }
+    }
+
+    let mut schema = serde_json::Map::new();
+    schema.insert("$schema".to_string(), JsonValue::from("https://json-schema.org/draft/2020-12/schema"));
+    schema.insert("type".to_string(), JsonValue::from("object"));
+    schema.insert("properties".to_string(), JsonValue::Object(properties));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), JsonValue::Array(required));
+    }
+    JsonValue::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 写入测试文件并推断结构
+    fn infer(name: &str, rows: &[&str]) -> SchemaReport {
+        let path = std::env::temp_dir().join(format!("smart-slice-schema-{}-{}.jsonl", std::process::id(), name));
+        let mut offsets = Vec::new();
+        let mut content = String::new();
+        for row in rows {
+            offsets.push(content.len() as u64);
+            content.push_str(row);
+            content.push('\n');
+        }
+        std::fs::write(&path, content).unwrap();
+        let report = infer_schema(path.to_str().unwrap(), &offsets, None, |_| {}).unwrap();
+        std::fs::remove_file(path).unwrap();
+        report
+    }
+
+    fn is_union(report: &SchemaReport, path: &str) -> bool {
+        report.fields.iter().find(|field| field.path == path).unwrap().union
+    }
+
+    #[test]
+    fn integer_and_number_are_not_a_union() {
+        let report = infer("numeric", &[
+            r#"{"plain":1,"nullable":1,"mixed":1}"#,
+            r#"{"plain":1.5,"nullable":2.5,"mixed":"one"}"#,
+            r#"{"plain":2,"nullable":null,"mixed":null}"#,
+        ]);
+        assert!(!is_union(&report, "plain"));
+        assert!(!is_union(&report, "nullable"));
+        assert!(is_union(&report, "mixed"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::jsonl::JsonPath;
+use crate::models::{AppResult, JsonObject, JsonValue, MatchLocation, SearchProgress};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// 判断一行是否包含关键词（关键词需已转为小写）
+pub fn line_matches(line: &str, keyword_lower: &str) -> bool {
+    line.to_lowercase().contains(keyword_lower)
+}
+
+/// 查找一行中所有包含关键词的字符串值
+///
+/// 返回每个命中值的路径及命中区间（按字符计数的 `[start, end)`），不区分大小写。
+pub fn find_match_locations(row: &JsonObject, keyword: &str) -> Vec<MatchLocation> {
+    let keyword: Vec<char> = keyword.to_lowercase().chars().collect();
+    let mut locations = Vec::new();
+    if keyword.is_empty() {
+        return locations;
+    }
+
+    // 按字段名排序，保证结果顺序稳定
+    let mut keys: Vec<&String> = row.keys().collect();
+    keys.sort();
+    for key in keys {
+        collect_matches(&row[key], &JsonPath::root().child_key(key), &keyword, &mut locations);
+    }
+
+    locations
+}
+
+/// 递归收集字符串值中的命中位置
+fn collect_matches(value: &JsonValue, path: &JsonPath, keyword: &[char], locations: &mut Vec<MatchLocation>) {
+    match value {
+        JsonValue::String(text) => {
+            let ranges = match_ranges(text, keyword);
+            if !ranges.is_empty() {
+                locations.push(MatchLocation {
+                    path: path.to_string(),
+                    ranges,
+                });
+            }
+        }
+        JsonValue::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_matches(item, &path.child_index(i), keyword, locations);
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, item) in map {
+                collect_matches(item, &path.child_key(key), keyword, locations);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 在文本中查找不重叠的命中区间（字符下标）
+fn match_ranges(text: &str, keyword: &[char]) -> Vec<[usize; 2]> {
+    // 小写化可能改变字符数量，记录每个小写字符对应的原字符下标
+    let mut lower = Vec::with_capacity(text.len());
+    let mut origin = Vec::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        for l in c.to_lowercase() {
+            lower.push(l);
+            origin.push(i);
+        }
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start + keyword.len() <= lower.len() {
+        if lower[start..start + keyword.len()] == *keyword {
+            let end = start + keyword.len();
+            ranges.push([origin[start], origin[end - 1] + 1]);
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+
+    ranges
+}
+
+/// 后台流式搜索会话
+///
+/// 在独立线程中扫描文件，匹配的行号持续追加，
+/// 调用方可以在匹配数足够时立即返回第一页，而扫描继续统计总数。
+pub struct SearchSession {
+    /// 会话编号
+    id: u64,
+
+    /// 文件路径
+    path: String,
+
+    /// 原始关键词
+    keyword: String,
+
+    /// 已找到的匹配行号（从0开始）
+    matches: Mutex<Vec<usize>>,
+
+    /// 是否已取消
+    cancelled: AtomicBool,
+
+    /// 最新进度
+    state: watch::Receiver<SearchProgress>,
+}
+
+impl SearchSession {
+    /// 启动新的搜索会话
+    pub fn start<F>(id: u64, path: &str, keyword: &str, progress_callback: F) -> AppResult<Arc<Self>>
+    where
+        F: Fn(SearchProgress) + Send + 'static,
+    {
+        let total_bytes = std::fs::metadata(path)?.len();
+        let file = File::open(path)?;
+
+        let initial = SearchProgress {
+            search_id: id,
+            keyword: keyword.to_string(),
+            bytes_scanned: 0,
+            total_bytes,
+            matches: 0,
+            percentage: 0.0,
+            done: false,
+            cancelled: false,
+        };
+        let (sender, receiver) = watch::channel(initial);
+
+        let session = Arc::new(Self {
+            id,
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            matches: Mutex::new(Vec::new()),
+            cancelled: AtomicBool::new(false),
+            state: receiver,
+        });
+
+        let worker = session.clone();
+        std::thread::spawn(move || {
+            worker.scan(BufReader::new(file), total_bytes, sender, progress_callback);
+        });
+
+        Ok(session)
+    }
+
+    /// 创建已完成的会话，用于索引直接给出全部匹配的情况
+    pub fn completed(id: u64, path: &str, keyword: &str, matches: Vec<usize>) -> Arc<Self> {
+        let total_bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let (_, receiver) = watch::channel(SearchProgress {
+            search_id: id,
+            keyword: keyword.to_string(),
+            bytes_scanned: total_bytes,
+            total_bytes,
+            matches: matches.len(),
+            percentage: 100.0,
+            done: true,
+            cancelled: false,
+        });
+
+        Arc::new(Self {
+            id,
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            matches: Mutex::new(matches),
+            cancelled: AtomicBool::new(false),
+            state: receiver,
+        })
+    }
+
+    /// 扫描文件并记录匹配行
+    fn scan<F>(&self, mut reader: BufReader<File>, total_bytes: u64, sender: watch::Sender<SearchProgress>, progress_callback: F)
+    where
+        F: Fn(SearchProgress),
+    {
+        let keyword = self.keyword.to_lowercase();
+
+        // 进度事件的时间间隔与会话状态的刷新间隔（行数）
+        let progress_interval = Duration::from_millis(100);
+        let state_interval = 256;
+        let mut last_progress_time = Instant::now();
+
+        let mut bytes_scanned = 0u64;
+        let mut match_count = 0usize;
+        let mut line_num = 0usize;
+        let mut line = String::new();
+
+        let progress = |bytes_scanned: u64, matches: usize, done: bool, cancelled: bool| SearchProgress {
+            search_id: self.id,
+            keyword: self.keyword.clone(),
+            bytes_scanned,
+            total_bytes,
+            matches,
+            percentage: if total_bytes > 0 {
+                (bytes_scanned as f32 / total_bytes as f32) * 100.0
+            } else {
+                100.0
+            },
+            done,
+            cancelled,
+        };
+
+        loop {
+            if self.is_cancelled() {
+                println!("搜索已取消: 关键词='{}'", self.keyword);
+                let state = progress(bytes_scanned, match_count, true, true);
+                let _ = sender.send(state.clone());
+                progress_callback(state);
+                return;
+            }
+
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) => break, // 文件结束
+                Ok(bytes) => {
+                    bytes_scanned += bytes as u64;
+                    let trimmed = line.trim();
+                    if !trimmed.is_empty() && line_matches(trimmed, &keyword) {
+                        if let Ok(mut matches) = self.matches.lock() {
+                            matches.push(line_num);
+                            match_count = matches.len();
+                        }
+                    }
+                    line_num += 1;
+                }
+                Err(e) => {
+                    println!("搜索读取行失败: {}", e);
+                    break;
+                }
+            }
+
+            // 定期刷新会话状态，唤醒等待第一页的请求
+            if line_num.is_multiple_of(state_interval) && match_count != sender.borrow().matches {
+                let _ = sender.send(progress(bytes_scanned, match_count, false, false));
+            }
+
+            let now = Instant::now();
+            if now.duration_since(last_progress_time) >= progress_interval {
+                last_progress_time = now;
+                progress_callback(progress(bytes_scanned, match_count, false, false));
+            }
+        }
+
+        let state = progress(bytes_scanned, match_count, true, false);
+        let _ = sender.send(state.clone());
+        progress_callback(state);
+    }
+
+    /// 会话编号
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 搜索的文件路径
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 搜索关键词
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    /// 取消搜索
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 当前进度
+    pub fn progress(&self) -> SearchProgress {
+        self.state.borrow().clone()
+    }
+
+    /// 等待匹配数达到 `count` 或搜索结束，返回当时的进度
+    pub async fn wait_for(&self, count: usize) -> SearchProgress {
+        let mut state = self.state.clone();
+        loop {
+            let current = state.borrow_and_update().clone();
+            if current.done || current.matches >= count {
+                return current;
+            }
+            if state.changed().await.is_err() {
+                // 扫描线程已退出
+                return state.borrow().clone();
+            }
+        }
+    }
+
+    /// 获取指定区间内的匹配行号
+    pub fn matches_range(&self, start: usize, end: usize) -> Vec<usize> {
+        match self.matches.lock() {
+            Ok(matches) => {
+                let end = end.min(matches.len());
+                if start >= end {
+                    Vec::new()
+                } else {
+                    matches[start..end].to_vec()
+                }
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+}
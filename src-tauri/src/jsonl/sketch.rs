@@ -0,0 +1,288 @@
+use crate::models::{NumericSummary, Quantile};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// 报告的分位点
+pub const QUANTILES: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+/// HyperLogLog 寄存器位数，误差约 0.8%
+const HLL_PRECISION: u32 = 14;
+
+/// 分位数草图的相对误差
+const QUANTILE_ACCURACY: f64 = 0.01;
+
+/// 计算一个值的 64 位哈希
+fn hash64<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// HyperLogLog 基数估计
+#[derive(Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// 创建空的估计器
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_PRECISION],
+        }
+    }
+
+    /// 加入一个值
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T) {
+        let hash = hash64(value);
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// 合并另一个估计器
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (a, b) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *a = (*a).max(*b);
+        }
+    }
+
+    /// 估计不同值的数量
+    pub fn estimate(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = 0.7213 / (1.0 + 1.079 / m) * m * m / sum;
+
+        // 基数较小时使用线性计数修正
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as usize
+        } else {
+            raw.round() as usize
+        }
+    }
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 可合并的数值摘要：精确的计数、极值、均值与方差，以及近似分位数
+///
+/// 分位数使用对数分桶（DDSketch），每个分位数的相对误差不超过 1%。
+#[derive(Clone, Default)]
+pub struct NumericSketch {
+    count: usize,
+    min: f64,
+    max: f64,
+    mean: f64,
+
+    /// 与均值之差的平方和
+    m2: f64,
+
+    /// 正数所在的对数桶
+    positive: BTreeMap<i32, u64>,
+
+    /// 负数绝对值所在的对数桶
+    negative: BTreeMap<i32, u64>,
+
+    /// 零的个数
+    zeros: u64,
+}
+
+impl NumericSketch {
+    /// 对数桶的底数
+    fn gamma() -> f64 {
+        (1.0 + QUANTILE_ACCURACY) / (1.0 - QUANTILE_ACCURACY)
+    }
+
+    /// 正数所在的桶
+    fn bucket(value: f64) -> i32 {
+        (value.ln() / Self::gamma().ln()).ceil() as i32
+    }
+
+    /// 桶的代表值
+    fn bucket_value(index: i32) -> f64 {
+        let gamma = Self::gamma();
+        2.0 * gamma.powi(index) / (gamma + 1.0)
+    }
+
+    /// 加入一个值，忽略 NaN 与无穷
+    pub fn insert(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+
+        if value.abs() < f64::MIN_POSITIVE {
+            self.zeros += 1;
+        } else if value > 0.0 {
+            *self.positive.entry(Self::bucket(value)).or_insert(0) += 1;
+        } else {
+            *self.negative.entry(Self::bucket(-value)).or_insert(0) += 1;
+        }
+    }
+
+    /// 合并另一个摘要
+    pub fn merge(&mut self, other: &NumericSketch) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        for (&index, &n) in &other.positive {
+            *self.positive.entry(index).or_insert(0) += n;
+        }
+        for (&index, &n) in &other.negative {
+            *self.negative.entry(index).or_insert(0) += n;
+        }
+        self.zeros += other.zeros;
+    }
+
+    /// 值的个数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 近似分位数
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (q.clamp(0.0, 1.0) * (self.count - 1) as f64).round() as u64;
+        let mut seen = 0u64;
+
+        // 从最小值开始：负数按绝对值从大到小，然后是零，最后是正数
+        for (&index, &n) in self.negative.iter().rev() {
+            seen += n;
+            if seen > rank {
+                return Some((-Self::bucket_value(index)).clamp(self.min, self.max));
+            }
+        }
+        seen += self.zeros;
+        if seen > rank {
+            return Some(0.0);
+        }
+        for (&index, &n) in &self.positive {
+            seen += n;
+            if seen > rank {
+                return Some(Self::bucket_value(index).clamp(self.min, self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// 生成摘要，没有值时返回 `None`
+    pub fn summary(&self) -> Option<NumericSummary> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(NumericSummary {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            std_dev: (self.m2 / self.count as f64).sqrt(),
+            quantiles: QUANTILES
+                .iter()
+                .filter_map(|&q| self.quantile(q).map(|value| Quantile { q, value }))
+                .collect(),
+        })
+    }
+}
+
+/// 高频值统计（Misra-Gries）
+///
+/// 不同值的数量超过容量时淘汰低频值，此时计数为下界。
+#[derive(Clone)]
+pub struct FrequentValues {
+    counts: HashMap<String, u64>,
+    capacity: usize,
+    exact: bool,
+}
+
+impl FrequentValues {
+    /// 创建统计器，最多精确跟踪 `capacity` 个值
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            counts: HashMap::new(),
+            capacity: capacity.max(1),
+            exact: true,
+        }
+    }
+
+    /// 加入一个值
+    pub fn insert(&mut self, key: &str) {
+        match self.counts.get_mut(key) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert(key.to_string(), 1);
+                if self.counts.len() > self.capacity * 2 {
+                    self.prune();
+                }
+            }
+        }
+    }
+
+    /// 合并另一个统计器
+    pub fn merge(&mut self, other: FrequentValues) {
+        self.exact &= other.exact;
+        for (key, count) in other.counts {
+            *self.counts.entry(key).or_insert(0) += count;
+        }
+        if self.counts.len() > self.capacity * 2 {
+            self.prune();
+        }
+    }
+
+    /// 只保留前 `capacity` 个值，其余值的计数从所有计数中扣除
+    fn prune(&mut self) {
+        let mut counts: Vec<u64> = self.counts.values().copied().collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        let threshold = counts.get(self.capacity).copied().unwrap_or(0);
+        if threshold == 0 {
+            return;
+        }
+        self.counts.retain(|_, count| {
+            *count = count.saturating_sub(threshold);
+            *count > 0
+        });
+        self.exact = false;
+    }
+
+    /// 计数是否精确
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// 出现次数最多的 `k` 个值，按次数降序
+    pub fn top(&self, k: usize) -> Vec<(&str, u64)> {
+        let mut items: Vec<(&str, u64)> = self.counts.iter().map(|(key, &count)| (key.as_str(), count)).collect();
+        items.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        items.truncate(k);
+        items
+    }
+}
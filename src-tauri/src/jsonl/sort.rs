@@ -0,0 +1,477 @@
+use crate::jsonl::{EditJournal, JsonPath, RowId};
+use crate::models::{AppError, AppResult, JsonValue, LoadingProgress};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+
+/// 单个归并段允许占用的内存（估算值）
+const RUN_MEMORY_BUDGET: usize = 128 * 1024 * 1024;
+
+/// 临时文件编号
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 排序键
+///
+/// 同类型之间按值比较，不同类型按 布尔 < 数字 < 字符串 < 数组/对象 排列；
+/// 缺失与 null 无论升序降序都排在最后。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SortKey {
+    /// 字段不存在
+    Missing,
+
+    /// null
+    Null,
+
+    /// 布尔值
+    Bool(bool),
+
+    /// 数字
+    Number(f64),
+
+    /// 字符串
+    String(String),
+
+    /// 数组或对象，按序列化文本比较
+    Other(String),
+}
+
+impl SortKey {
+    /// 从一行中提取排序键
+    pub fn extract(row: &JsonValue, path: &JsonPath) -> Self {
+        match path.get(row) {
+            None => Self::Missing,
+            Some(JsonValue::Null) => Self::Null,
+            Some(JsonValue::Bool(b)) => Self::Bool(*b),
+            Some(JsonValue::Number(n)) => n.as_f64().map(Self::Number).unwrap_or(Self::Null),
+            Some(JsonValue::String(s)) => Self::String(s.clone()),
+            Some(other) => Self::Other(other.to_string()),
+        }
+    }
+
+    /// 是否为空值（缺失或 null）
+    fn is_empty(&self) -> bool {
+        matches!(self, Self::Missing | Self::Null)
+    }
+
+    /// 类型顺序
+    fn type_rank(&self) -> u8 {
+        match self {
+            Self::Bool(_) => 0,
+            Self::Number(_) => 1,
+            Self::String(_) => 2,
+            Self::Other(_) => 3,
+            Self::Null => 4,
+            Self::Missing => 5,
+        }
+    }
+
+    /// 估算占用的内存
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + match self {
+                Self::String(s) | Self::Other(s) => s.len(),
+                _ => 0,
+            }
+    }
+
+    /// 比较两个排序键，空值始终排在最后
+    pub fn compare(&self, other: &Self, descending: bool) -> Ordering {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => return self.type_rank().cmp(&other.type_rank()),
+            (true, false) => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            (false, false) => {}
+        }
+
+        let ordering = match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Number(a), Self::Number(b)) => a.total_cmp(b),
+            (Self::String(a), Self::String(b)) | (Self::Other(a), Self::Other(b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        };
+
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// 排序条目：行号与排序键
+#[derive(Debug, Serialize, Deserialize)]
+struct SortEntry(usize, SortKey);
+
+impl SortEntry {
+    /// 比较两个条目，键相同时按行号保持稳定
+    fn compare(&self, other: &Self, descending: bool) -> Ordering {
+        self.1.compare(&other.1, descending).then(self.0.cmp(&other.0))
+    }
+}
+
+/// 归并时堆中的元素
+struct HeapItem {
+    entry: SortEntry,
+    run: usize,
+    descending: bool,
+}
+
+impl PartialEq for HeapItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapItem {}
+
+impl PartialOrd for HeapItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.entry.compare(&other.entry, self.descending)
+    }
+}
+
+/// 排序后的行号排列
+///
+/// 排列以小端 u64 写入临时文件并通过内存映射访问，释放时删除临时文件。
+pub struct SortOrder {
+    /// 排序字段路径
+    key_path: String,
+
+    /// 是否降序
+    descending: bool,
+
+    /// 排列文件
+    file_path: PathBuf,
+
+    /// 排列文件的内存映射
+    mmap: Option<Mmap>,
+
+    /// 行数
+    len: usize,
+}
+
+impl SortOrder {
+    /// 按字段路径对文件排序，数据超过内存预算时使用外部归并排序
+    ///
+    /// 排序键取自应用 `journal` 中编辑后的行，`journal` 不能包含增删行。
+    pub fn build<F>(path: &str, key_path: &str, descending: bool, journal: &EditJournal, progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        Self::build_with_budget(path, key_path, descending, journal, RUN_MEMORY_BUDGET, progress_callback)
+    }
+
+    /// 按字段路径对文件排序，单个归并段最多占用 `run_budget` 字节
+    fn build_with_budget<F>(path: &str, key_path: &str, descending: bool, journal: &EditJournal, run_budget: usize, progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let json_path = JsonPath::parse(key_path)?;
+        let file_size = std::fs::metadata(path)?.len();
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let progress_interval = Duration::from_millis(100);
+        let mut last_progress_time = Instant::now();
+
+        let mut runs = Vec::new();
+        let mut chunk: Vec<SortEntry> = Vec::new();
+        let mut chunk_memory = 0usize;
+        let mut bytes_read = 0u64;
+        let mut line_num = 0usize;
+        let mut line = String::new();
+
+        // 提取排序键，内存超出预算时写出一个有序归并段
+        loop {
+            line.clear();
+            let bytes = reader.read_line(&mut line)?;
+            if bytes == 0 {
+                break;
+            }
+            bytes_read += bytes as u64;
+
+            let row = if journal.is_edited(RowId::Original(line_num)) {
+                journal.edited_row(line_num, line.trim())
+            } else {
+                serde_json::from_str::<JsonValue>(line.trim()).ok()
+            };
+            let key = row.map_or(SortKey::Missing, |row| SortKey::extract(&row, &json_path));
+            chunk_memory += key.memory_size() + std::mem::size_of::<SortEntry>();
+            chunk.push(SortEntry(line_num, key));
+            line_num += 1;
+
+            if chunk_memory >= run_budget {
+                runs.push(Self::write_run(&mut chunk, descending)?);
+                chunk_memory = 0;
+            }
+
+            let now = Instant::now();
+            if now.duration_since(last_progress_time) >= progress_interval {
+                last_progress_time = now;
+                progress_callback(LoadingProgress {
+                    current: line_num,
+                    total: line_num.max((line_num as u64 * file_size / bytes_read.max(1)) as usize),
+                    stage: "提取排序键".to_string(),
+                    percentage: if file_size > 0 {
+                        (bytes_read as f32 / file_size as f32) * 100.0
+                    } else {
+                        100.0
+                    },
+                });
+            }
+        }
+
+        let file_path = temp_path("perm");
+        let mut writer = BufWriter::new(File::create(&file_path)?);
+
+        if runs.is_empty() {
+            // 数据可以完全放入内存
+            chunk.sort_by(|a, b| a.compare(b, descending));
+            for entry in &chunk {
+                writer.write_all(&(entry.0 as u64).to_le_bytes())?;
+            }
+        } else {
+            if !chunk.is_empty() {
+                runs.push(Self::write_run(&mut chunk, descending)?);
+            }
+            progress_callback(LoadingProgress {
+                current: 0,
+                total: line_num,
+                stage: format!("归并 {} 个有序段", runs.len()),
+                percentage: 0.0,
+            });
+            let merged = Self::merge_runs(&runs, descending, line_num, &mut writer, &progress_callback);
+            for run in &runs {
+                let _ = std::fs::remove_file(run);
+            }
+            merged?;
+        }
+        writer.flush()?;
+        drop(writer);
+
+        progress_callback(LoadingProgress {
+            current: line_num,
+            total: line_num,
+            stage: "排序完成".to_string(),
+            percentage: 100.0,
+        });
+
+        let mut order = Self {
+            key_path: key_path.to_string(),
+            descending,
+            file_path,
+            mmap: None,
+            len: line_num,
+        };
+        if line_num > 0 {
+            let file = File::open(&order.file_path)?;
+            // 安全性：排列文件由本对象独占，映射期间不会被修改
+            order.mmap = Some(unsafe { Mmap::map(&file)? });
+        }
+
+        Ok(order)
+    }
+
+    /// 将当前块排序后写入归并段文件
+    fn write_run(chunk: &mut Vec<SortEntry>, descending: bool) -> AppResult<PathBuf> {
+        chunk.sort_by(|a, b| a.compare(b, descending));
+
+        let run_path = temp_path("run");
+        let mut writer = BufWriter::new(File::create(&run_path)?);
+        for entry in chunk.drain(..) {
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+
+        println!("写出排序归并段: {}", run_path.display());
+        Ok(run_path)
+    }
+
+    /// 多路归并所有有序段，输出行号排列
+    fn merge_runs<W, F>(runs: &[PathBuf], descending: bool, total: usize, writer: &mut W, progress_callback: &F) -> AppResult<()>
+    where
+        W: Write,
+        F: Fn(LoadingProgress),
+    {
+        let mut readers = runs
+            .iter()
+            .map(|run| File::open(run).map(|file| BufReader::new(file).lines()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let read_next = |reader: &mut std::io::Lines<BufReader<File>>| -> AppResult<Option<SortEntry>> {
+            match reader.next() {
+                Some(line) => Ok(Some(serde_json::from_str(&line?)?)),
+                None => Ok(None),
+            }
+        };
+
+        // BinaryHeap 是最大堆，用 Reverse 取最小条目
+        let mut heap = BinaryHeap::new();
+        for (run, reader) in readers.iter_mut().enumerate() {
+            if let Some(entry) = read_next(reader)? {
+                heap.push(Reverse(HeapItem { entry, run, descending }));
+            }
+        }
+
+        let progress_interval = Duration::from_millis(100);
+        let mut last_progress_time = Instant::now();
+        let mut written = 0usize;
+
+        while let Some(Reverse(item)) = heap.pop() {
+            writer.write_all(&(item.entry.0 as u64).to_le_bytes())?;
+            written += 1;
+
+            if let Some(entry) = read_next(&mut readers[item.run])? {
+                heap.push(Reverse(HeapItem { entry, run: item.run, descending }));
+            }
+
+            let now = Instant::now();
+            if now.duration_since(last_progress_time) >= progress_interval {
+                last_progress_time = now;
+                progress_callback(LoadingProgress {
+                    current: written,
+                    total,
+                    stage: "归并排序".to_string(),
+                    percentage: (written as f32 / total.max(1) as f32) * 100.0,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 排序字段路径
+    pub fn key_path(&self) -> &str {
+        &self.key_path
+    }
+
+    /// 是否降序
+    pub fn descending(&self) -> bool {
+        self.descending
+    }
+
+    /// 行数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 获取排序后第 `position` 位对应的原始行号
+    pub fn line_at(&self, position: usize) -> Option<usize> {
+        if position >= self.len {
+            return None;
+        }
+        let mmap = self.mmap.as_ref()?;
+        let start = position * 8;
+        let bytes: [u8; 8] = mmap.get(start..start + 8)?.try_into().ok()?;
+        Some(u64::from_le_bytes(bytes) as usize)
+    }
+
+    /// 获取排序后区间 `[start, end)` 对应的原始行号
+    pub fn lines(&self, start: usize, end: usize) -> AppResult<Vec<usize>> {
+        (start..end.min(self.len))
+            .map(|position| {
+                self.line_at(position)
+                    .ok_or_else(|| AppError::General("排序索引已损坏".to_string()))
+            })
+            .collect()
+    }
+}
+
+impl Drop for SortOrder {
+    fn drop(&mut self) {
+        self.mmap = None;
+        let _ = std::fs::remove_file(&self.file_path);
+    }
+}
+
+/// 生成临时文件路径
+fn temp_path(kind: &str) -> PathBuf {
+    let n = TEMP_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+    Path::new(&std::env::temp_dir()).join(format!("smart-slice-sort-{}-{}-{}.tmp", std::process::id(), kind, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// 写入测试文件
+    fn write_rows(name: &str, rows: &[JsonValue]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("smart-slice-sort-test-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.jsonl");
+        let lines: Vec<String> = rows.iter().map(JsonValue::to_string).collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
+
+    fn sort(path: &Path, key_path: &str, descending: bool, journal: &EditJournal, run_budget: usize) -> Vec<usize> {
+        let order = SortOrder::build_with_budget(path.to_str().unwrap(), key_path, descending, journal, run_budget, |_| {}).unwrap();
+        order.lines(0, order.len()).unwrap()
+    }
+
+    #[test]
+    fn orders_by_type_and_keeps_empty_last() {
+        let path = write_rows("types", &[
+            json!({ "v": "b" }),
+            json!({ "v": null }),
+            json!({ "v": 2 }),
+            json!({}),
+            json!({ "v": true }),
+            json!({ "v": 1.5 }),
+            json!({ "v": "a" }),
+            json!({ "v": [1] }),
+        ]);
+        let journal = EditJournal::default();
+
+        assert_eq!(sort(&path, "v", false, &journal, RUN_MEMORY_BUDGET), vec![4, 5, 2, 6, 0, 7, 1, 3]);
+        assert_eq!(sort(&path, "v", true, &journal, RUN_MEMORY_BUDGET), vec![7, 0, 6, 2, 5, 4, 1, 3]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn external_merge_matches_in_memory_sort() {
+        // 键有重复，用于检查归并后相同键仍按行号保持稳定
+        let rows: Vec<JsonValue> = (0..200).map(|i| json!({ "n": (i * 37) % 50, "s": format!("{:03}", (i * 11) % 17) })).collect();
+        let path = write_rows("merge", &rows);
+        let journal = EditJournal::default();
+
+        for (key_path, descending) in [("n", false), ("n", true), ("s", false)] {
+            let in_memory = sort(&path, key_path, descending, &journal, RUN_MEMORY_BUDGET);
+            // 每个归并段只容纳几行，强制走外部归并
+            let merged = sort(&path, key_path, descending, &journal, 256);
+            assert_eq!(merged, in_memory);
+            assert_eq!(merged.len(), rows.len());
+        }
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn sorts_by_edited_values() {
+        let path = write_rows("edited", &[json!({ "v": 1 }), json!({ "v": 2 }), json!({ "v": 3 })]);
+        let mut journal = EditJournal::default();
+        let mut row = json!({ "v": 2 });
+        journal.set_value(RowId::Original(1), &mut row, JsonPath::parse("v").unwrap(), json!(0)).unwrap();
+
+        assert_eq!(sort(&path, "v", false, &journal, RUN_MEMORY_BUDGET), vec![1, 0, 2]);
+        assert_eq!(sort(&path, "v", false, &journal, 64), vec![1, 0, 2]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
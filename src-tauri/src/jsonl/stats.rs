@@ -0,0 +1,244 @@
+use crate::jsonl::{canonical_json, type_name, FrequentValues, HyperLogLog, JsonPath, MappedLines, NumericSketch};
+use crate::models::{AppError, AppResult, FieldStatistics, FieldType, HistogramBucket, JsonValue, LengthSummary, LoadingProgress, ValueCount};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// 每批并行处理的行数，每批结束后报告一次进度
+const BATCH_LINES: usize = 50_000;
+
+/// 高频值统计精确跟踪的值数量
+const FREQUENT_CAPACITY: usize = 1000;
+
+/// 高频值中字符串的最大字符数
+const MAX_VALUE_CHARS: usize = 200;
+
+/// 单个字段的统计累加器
+#[derive(Clone)]
+struct FieldAccumulator {
+    present: usize,
+    nulls: usize,
+    types: HashMap<&'static str, usize>,
+    numbers: NumericSketch,
+    lengths: NumericSketch,
+
+    /// 字符串长度直方图，第 `i` 个桶为 `[2^(i-1), 2^i)`，第 0 个桶为空字符串
+    length_buckets: Vec<usize>,
+    frequent: FrequentValues,
+    distinct: HyperLogLog,
+}
+
+impl FieldAccumulator {
+    fn new() -> Self {
+        Self {
+            present: 0,
+            nulls: 0,
+            types: HashMap::new(),
+            numbers: NumericSketch::default(),
+            lengths: NumericSketch::default(),
+            length_buckets: Vec::new(),
+            frequent: FrequentValues::new(FREQUENT_CAPACITY),
+            distinct: HyperLogLog::new(),
+        }
+    }
+
+    /// 记录一个值
+    fn record(&mut self, value: &JsonValue, key: &mut String) {
+        self.present += 1;
+        *self.types.entry(type_name(value)).or_insert(0) += 1;
+
+        match value {
+            JsonValue::Null => self.nulls += 1,
+            JsonValue::Number(n) => {
+                if let Some(n) = n.as_f64() {
+                    self.numbers.insert(n);
+                }
+            }
+            JsonValue::String(text) => {
+                let len = text.chars().count();
+                self.lengths.insert(len as f64);
+                let bucket = (usize::BITS - len.leading_zeros()) as usize;
+                if self.length_buckets.len() <= bucket {
+                    self.length_buckets.resize(bucket + 1, 0);
+                }
+                self.length_buckets[bucket] += 1;
+            }
+            _ => {}
+        }
+
+        key.clear();
+        canonical_json(value, key);
+        self.distinct.insert(key.as_str());
+        match value {
+            JsonValue::String(text) if text.chars().count() > MAX_VALUE_CHARS => {
+                let truncated: String = text.chars().take(MAX_VALUE_CHARS).collect();
+                key.clear();
+                canonical_json(&JsonValue::String(format!("{}…", truncated)), key);
+            }
+            _ => {}
+        }
+        self.frequent.insert(key);
+    }
+
+    /// 合并另一部分行的统计
+    fn merge(&mut self, other: FieldAccumulator) {
+        self.present += other.present;
+        self.nulls += other.nulls;
+        for (name, count) in other.types {
+            *self.types.entry(name).or_insert(0) += count;
+        }
+        self.numbers.merge(&other.numbers);
+        self.lengths.merge(&other.lengths);
+        if self.length_buckets.len() < other.length_buckets.len() {
+            self.length_buckets.resize(other.length_buckets.len(), 0);
+        }
+        for (bucket, count) in other.length_buckets.into_iter().enumerate() {
+            self.length_buckets[bucket] += count;
+        }
+        self.frequent.merge(other.frequent);
+        self.distinct.merge(&other.distinct);
+    }
+}
+
+/// 一部分行的统计结果
+#[derive(Clone)]
+struct StatsAccumulator {
+    rows: usize,
+    fields: Vec<FieldAccumulator>,
+}
+
+impl StatsAccumulator {
+    fn new(field_count: usize) -> Self {
+        Self {
+            rows: 0,
+            fields: vec![FieldAccumulator::new(); field_count],
+        }
+    }
+
+    fn merge(mut self, other: StatsAccumulator) -> Self {
+        self.rows += other.rows;
+        for (field, other_field) in self.fields.iter_mut().zip(other.fields) {
+            field.merge(other_field);
+        }
+        self
+    }
+}
+
+/// 一次并行扫描计算多个字段的统计
+///
+/// `lines` 为参与统计的行号；`top_k` 为每个字段返回的高频值数量。
+pub fn field_statistics<F>(
+    path: &str,
+    line_offsets: &[u64],
+    lines: &[usize],
+    field_paths: &[String],
+    top_k: usize,
+    progress_callback: F,
+) -> AppResult<(usize, Vec<FieldStatistics>)>
+where
+    F: Fn(LoadingProgress),
+{
+    if field_paths.is_empty() {
+        return Err(AppError::General("请至少选择一个字段".to_string()));
+    }
+    let paths = field_paths
+        .iter()
+        .map(|path| JsonPath::parse(path))
+        .collect::<AppResult<Vec<_>>>()?;
+    let mapped = MappedLines::open(path, line_offsets)?;
+
+    let mut result = StatsAccumulator::new(paths.len());
+    let mut done = 0;
+    for batch in lines.chunks(BATCH_LINES) {
+        let partial = batch
+            .par_iter()
+            .fold(
+                || (StatsAccumulator::new(paths.len()), String::new()),
+                |(mut acc, mut key), &line_num| {
+                    let row = mapped
+                        .line(line_num)
+                        .filter(|line| !line.is_empty())
+                        .and_then(|line| serde_json::from_str::<JsonValue>(line).ok());
+                    if let Some(row) = row {
+                        acc.rows += 1;
+                        for (field, path) in acc.fields.iter_mut().zip(&paths) {
+                            if let Some(value) = path.get(&row) {
+                                field.record(value, &mut key);
+                            }
+                        }
+                    }
+                    (acc, key)
+                },
+            )
+            .map(|(acc, _)| acc)
+            .reduce(|| StatsAccumulator::new(paths.len()), StatsAccumulator::merge);
+        result = result.merge(partial);
+
+        done += batch.len();
+        progress_callback(LoadingProgress {
+            current: done,
+            total: lines.len(),
+            stage: "计算字段统计".to_string(),
+            percentage: done as f32 / lines.len() as f32 * 100.0,
+        });
+    }
+
+    let rows = result.rows;
+    let stats = result
+        .fields
+        .into_iter()
+        .zip(field_paths)
+        .map(|(field, path)| {
+            let mut types: Vec<FieldType> = field
+                .types
+                .iter()
+                .map(|(name, count)| FieldType {
+                    name: name.to_string(),
+                    count: *count,
+                })
+                .collect();
+            types.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+
+            let length = field.lengths.summary().map(|summary| LengthSummary {
+                summary,
+                histogram: field
+                    .length_buckets
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &count)| count > 0)
+                    .map(|(bucket, &count)| HistogramBucket {
+                        start: if bucket == 0 { 0.0 } else { (1u64 << (bucket - 1)) as f64 },
+                        end: if bucket == 0 { 1.0 } else { (1u64 << bucket) as f64 },
+                        count,
+                    })
+                    .collect(),
+            });
+
+            let top_values = field
+                .frequent
+                .top(top_k)
+                .into_iter()
+                .filter_map(|(key, count)| {
+                    serde_json::from_str::<JsonValue>(key).ok().map(|value| ValueCount {
+                        value,
+                        count: count as usize,
+                    })
+                })
+                .collect();
+
+            FieldStatistics {
+                path: path.clone(),
+                present: field.present,
+                missing: rows.saturating_sub(field.present),
+                nulls: field.nulls,
+                types,
+                numeric: field.numbers.summary(),
+                length,
+                top_values,
+                top_values_exact: field.frequent.is_exact(),
+                distinct_estimate: field.distinct.estimate(),
+            }
+        })
+        .collect();
+
+    Ok((rows, stats))
+}
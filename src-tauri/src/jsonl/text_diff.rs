@@ -0,0 +1,231 @@
+use crate::models::TextSegment;
+
+/// 最多计算的编辑次数，超过后把不同的部分整体记为删除加插入
+const MAX_EDITS: usize = 1000;
+
+/// 参与字符级比较的最大总字符数
+pub const MAX_TEXT_DIFF_CHARS: usize = 200_000;
+
+/// 编辑操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Insert,
+    Delete,
+}
+
+impl Op {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Equal => "equal",
+            Self::Insert => "insert",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+/// Myers 差分算法，返回把 `a` 变为 `b` 的编辑序列
+///
+/// 编辑次数超过 `max_edits` 时返回 `None`。
+fn myers(a: &[char], b: &[char], max_edits: usize) -> Option<Vec<Op>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let limit = (a.len() + b.len()).min(max_edits) as isize;
+    let offset = limit + 1;
+    let mut v = vec![0isize; (2 * offset + 1) as usize];
+
+    // 每一轮开始前 `v` 中 [-(d+1), d+1] 范围内的值，用于回溯
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..=limit {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let index = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+/// 根据每一轮的状态回溯出编辑序列
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Op> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let get = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push(Op::Insert);
+                y -= 1;
+            } else {
+                ops.push(Op::Delete);
+                x -= 1;
+            }
+        }
+    }
+    ops.reverse();
+    ops
+}
+
+/// 追加一段文本，与上一段操作相同时合并
+fn push_segment(segments: &mut Vec<TextSegment>, op: Op, text: &[char]) {
+    if text.is_empty() {
+        return;
+    }
+    match segments.last_mut() {
+        Some(last) if last.op == op.name() => last.text.extend(text),
+        _ => segments.push(TextSegment {
+            op: op.name().to_string(),
+            text: text.iter().collect(),
+        }),
+    }
+}
+
+/// 字符级比较两个字符串
+///
+/// 先去掉相同的前缀与后缀，再对中间部分做 Myers 差分；
+/// 差异过多时中间部分整体记为删除加插入。
+pub fn text_diff(left: &str, right: &str) -> Vec<TextSegment> {
+    let a: Vec<char> = left.chars().collect();
+    let b: Vec<char> = right.chars().collect();
+
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut segments = Vec::new();
+    push_segment(&mut segments, Op::Equal, &a[..prefix]);
+    match myers(a_mid, b_mid, MAX_EDITS) {
+        Some(ops) => {
+            let (mut i, mut j) = (0, 0);
+            for op in ops {
+                match op {
+                    Op::Equal => {
+                        push_segment(&mut segments, op, &a_mid[i..i + 1]);
+                        i += 1;
+                        j += 1;
+                    }
+                    Op::Delete => {
+                        push_segment(&mut segments, op, &a_mid[i..i + 1]);
+                        i += 1;
+                    }
+                    Op::Insert => {
+                        push_segment(&mut segments, op, &b_mid[j..j + 1]);
+                        j += 1;
+                    }
+                }
+            }
+        }
+        None => {
+            push_segment(&mut segments, Op::Delete, a_mid);
+            push_segment(&mut segments, Op::Insert, b_mid);
+        }
+    }
+    push_segment(&mut segments, Op::Equal, &a[a.len() - suffix..]);
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    /// 按编辑序列由 `a` 重建 `b`，返回重建结果与编辑次数
+    fn replay(a: &[char], b: &[char], ops: &[Op]) -> (Vec<char>, usize) {
+        let (mut i, mut j) = (0, 0);
+        let mut result = Vec::new();
+        let mut edits = 0;
+        for op in ops {
+            match op {
+                Op::Equal => {
+                    assert_eq!(a[i], b[j]);
+                    result.push(a[i]);
+                    i += 1;
+                    j += 1;
+                }
+                Op::Delete => {
+                    i += 1;
+                    edits += 1;
+                }
+                Op::Insert => {
+                    result.push(b[j]);
+                    j += 1;
+                    edits += 1;
+                }
+            }
+        }
+        assert_eq!((i, j), (a.len(), b.len()));
+        (result, edits)
+    }
+
+    fn segments(diff: &[TextSegment]) -> Vec<(&str, &str)> {
+        diff.iter().map(|segment| (segment.op.as_str(), segment.text.as_str())).collect()
+    }
+
+    #[test]
+    fn myers_finds_shortest_edit_script() {
+        for (a, b, shortest) in [("abcabba", "cbabac", 5), ("", "abc", 3), ("abc", "", 3), ("kitten", "sitting", 5), ("same", "same", 0)] {
+            let (a, b) = (chars(a), chars(b));
+            let ops = myers(&a, &b, MAX_EDITS).unwrap();
+            let (rebuilt, edits) = replay(&a, &b, &ops);
+            assert_eq!(rebuilt, b);
+            assert_eq!(edits, shortest);
+        }
+    }
+
+    #[test]
+    fn myers_gives_up_past_edit_limit() {
+        assert!(myers(&chars("abcdef"), &chars("uvwxyz"), 4).is_none());
+        assert!(myers(&chars("abcdef"), &chars("uvwxyz"), 12).is_some());
+    }
+
+    #[test]
+    fn text_diff_merges_adjacent_segments() {
+        let diff = text_diff("hello world", "hello brave new world");
+        assert_eq!(segments(&diff), vec![("equal", "hello "), ("insert", "brave new "), ("equal", "world")]);
+
+        let diff = text_diff("价格 100 元", "价格 120 元");
+        assert_eq!(segments(&diff), vec![("equal", "价格 1"), ("delete", "0"), ("insert", "2"), ("equal", "0 元")]);
+
+        assert!(text_diff("", "").is_empty());
+    }
+
+    #[test]
+    fn text_diff_replaces_whole_middle_when_too_different() {
+        let left: String = (0..MAX_EDITS).map(|i| char::from(b'a' + (i % 26) as u8)).collect();
+        let right: String = (0..MAX_EDITS).map(|i| char::from(b'A' + (i % 26) as u8)).collect();
+        let diff = text_diff(&format!("<{}>", left), &format!("<{}>", right));
+        assert_eq!(segments(&diff), vec![("equal", "<"), ("delete", left.as_str()), ("insert", right.as_str()), ("equal", ">")]);
+    }
+}
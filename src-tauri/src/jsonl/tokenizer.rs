@@ -0,0 +1,208 @@
+use crate::models::JsonValue;
+use jieba_rs::Jieba;
+use std::sync::OnceLock;
+
+/// 全局分词器，首次使用时加载内置词典
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+
+/// 获取分词器
+fn jieba() -> &'static Jieba {
+    JIEBA.get_or_init(Jieba::new)
+}
+
+/// 判断字符是否属于中日韩文字
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // 平假名、片假名
+        | 0x3400..=0x4DBF   // CJK 扩展 A
+        | 0x4E00..=0x9FFF   // CJK 统一汉字
+        | 0xAC00..=0xD7AF   // 韩文音节
+        | 0xF900..=0xFAFF   // CJK 兼容汉字
+        | 0x20000..=0x2FA1F // CJK 扩展 B 及以后
+    )
+}
+
+/// 将文本切分为小写的词元
+///
+/// 字母数字连续串作为一个词元；中日韩文字按词典分词（搜索模式，长词额外输出其中的短词），
+/// 词典无法识别的连续单字再补充二元组。
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk = String::new();
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(c);
+        } else {
+            flush_cjk(&mut cjk, &mut tokens);
+            if c.is_alphanumeric() || c == '_' {
+                word.extend(c.to_lowercase());
+            } else {
+                flush_word(&mut word, &mut tokens);
+            }
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+
+    tokens
+}
+
+/// 搜索关键词中可用于查找索引的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryTerm {
+    /// 字母数字词（小写）
+    ///
+    /// `starts_token` / `ends_token` 表示词的开头 / 结尾在关键词中与其他字符隔开，
+    /// 即任何匹配行中含有以该词开头 / 结尾的词元；两者都不成立时该词可能位于某个词元的中间。
+    Word {
+        text: String,
+        starts_token: bool,
+        ends_token: bool,
+    },
+
+    /// 中日韩单字
+    ///
+    /// 连续的中日韩文字按词典分词，任何一个字都落在某个词元之内，
+    /// 因此匹配行中必定有含有该字的词元。
+    Cjk(char),
+}
+
+/// 把搜索关键词切分为查询项，字符分类与 `tokenize` 相同
+///
+/// 中日韩文字逐字查找，重复的字只保留一次。
+pub fn query_terms(keyword: &str) -> Vec<QueryTerm> {
+    let mut terms = Vec::new();
+    let mut seen_cjk = Vec::new();
+    let mut word = String::new();
+    // 当前词之前是否有分隔字符
+    let mut bounded_before = false;
+
+    for c in keyword.chars() {
+        if !is_cjk(c) && (c.is_alphanumeric() || c == '_') {
+            word.extend(c.to_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            terms.push(QueryTerm::Word {
+                text: std::mem::take(&mut word),
+                starts_token: bounded_before,
+                ends_token: true,
+            });
+        }
+        if is_cjk(c) && !seen_cjk.contains(&c) {
+            seen_cjk.push(c);
+            terms.push(QueryTerm::Cjk(c));
+        }
+        bounded_before = true;
+    }
+    if !word.is_empty() {
+        terms.push(QueryTerm::Word {
+            text: word,
+            starts_token: bounded_before,
+            ends_token: false,
+        });
+    }
+
+    terms
+}
+
+/// 对一段连续的中日韩文字分词
+pub fn segment_cjk(run: &str, tokens: &mut Vec<String>) {
+    let mut singles: Vec<&str> = Vec::new();
+
+    for piece in jieba().cut_for_search(run, false) {
+        if piece.chars().count() == 1 {
+            singles.push(piece);
+        } else {
+            push_bigrams(&singles, tokens);
+            singles.clear();
+            tokens.push(piece.to_string());
+        }
+    }
+    push_bigrams(&singles, tokens);
+}
+
+/// 输出连续单字及其相邻二元组，作为未登录词的兜底
+fn push_bigrams(singles: &[&str], tokens: &mut Vec<String>) {
+    for (i, single) in singles.iter().enumerate() {
+        tokens.push(single.to_string());
+        if let Some(next) = singles.get(i + 1) {
+            tokens.push(format!("{}{}", single, next));
+        }
+    }
+}
+
+/// 收集一行 JSON 中字段名与标量值的全部词元
+pub fn tokenize_value(value: &JsonValue, tokens: &mut Vec<String>) {
+    match value {
+        JsonValue::String(text) => tokens.extend(tokenize(text)),
+        JsonValue::Number(n) => tokens.extend(tokenize(&n.to_string())),
+        JsonValue::Bool(b) => tokens.push(b.to_string()),
+        JsonValue::Null => tokens.push("null".to_string()),
+        JsonValue::Array(items) => {
+            for item in items {
+                tokenize_value(item, tokens);
+            }
+        }
+        JsonValue::Object(map) => {
+            for (key, item) in map {
+                tokens.extend(tokenize(key));
+                tokenize_value(item, tokens);
+            }
+        }
+    }
+}
+
+/// 将累积的中日韩文字分词后输出
+fn flush_cjk(cjk: &mut String, tokens: &mut Vec<String>) {
+    if !cjk.is_empty() {
+        segment_cjk(cjk, tokens);
+        cjk.clear();
+    }
+}
+
+/// 将累积的字母数字串作为词元输出
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    if !word.is_empty() {
+        tokens.push(std::mem::take(word));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_chinese_words() {
+        let tokens = tokenize("我们在北京大学学习");
+        assert!(tokens.contains(&"北京大学".to_string()));
+        assert!(tokens.contains(&"北京".to_string()));
+        assert!(tokens.contains(&"学习".to_string()));
+    }
+
+    #[test]
+    fn splits_words_and_cjk_runs() {
+        assert_eq!(tokenize("Hello_World 2024年"), vec!["hello_world", "2024", "年"]);
+    }
+
+    #[test]
+    fn query_terms_mark_token_boundaries() {
+        let word = |text: &str, starts_token, ends_token| QueryTerm::Word { text: text.to_string(), starts_token, ends_token };
+        assert_eq!(query_terms("Error"), vec![word("error", false, false)]);
+        assert_eq!(query_terms("disk full error"), vec![
+            word("disk", false, true),
+            word("full", true, true),
+            word("error", true, false),
+        ]);
+        assert_eq!(query_terms("v2中文文档"), vec![
+            word("v2", false, true),
+            QueryTerm::Cjk('中'),
+            QueryTerm::Cjk('文'),
+            QueryTerm::Cjk('档'),
+        ]);
+        assert!(query_terms(" \"\": ").is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+use crate::models::{AppError, AppResult, JsonObject, JsonValue};
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, Filter, Native, RcIter};
+use jaq_json::Val;
+
+/// jq 风格的行变换程序
+///
+/// 使用 jaq 编译 jq 语法的程序，对每一行 JSON 单独求值，
+/// 例如 `{q: .messages[0].content, a: .messages[-1].content}`。
+pub struct RowTransform {
+    /// 原始程序文本
+    program: String,
+
+    /// 编译后的过滤器
+    filter: Filter<Native<Val>>,
+}
+
+impl RowTransform {
+    /// 编译变换程序
+    pub fn compile(program: &str) -> AppResult<Self> {
+        let program = program.trim();
+        if program.is_empty() {
+            return Err(AppError::Transform("变换程序为空".to_string()));
+        }
+
+        let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+        let arena = Arena::default();
+
+        // 解析程序
+        let modules = loader
+            .load(&arena, File { code: program, path: () })
+            .map_err(|errs| {
+                let detail: Vec<String> = errs.iter().map(|(_, e)| format!("{:?}", e)).collect();
+                AppError::Transform(format!("变换程序解析失败: {}", detail.join("; ")))
+            })?;
+
+        // 编译程序
+        let filter = Compiler::default()
+            .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+            .compile(modules)
+            .map_err(|errs| {
+                let detail: Vec<String> = errs
+                    .iter()
+                    .flat_map(|(_, es)| es.iter().map(|(name, kind)| format!("未定义的{} {}", kind.as_str(), name)))
+                    .collect();
+                AppError::Transform(format!("变换程序编译失败: {}", detail.join("; ")))
+            })?;
+
+        Ok(Self {
+            program: program.to_string(),
+            filter,
+        })
+    }
+
+    /// 获取原始程序文本
+    pub fn program(&self) -> &str {
+        &self.program
+    }
+
+    /// 对单个 JSON 值求值
+    ///
+    /// 程序没有输出时返回 null，输出多个值时合并为数组。
+    pub fn apply(&self, value: JsonValue) -> Result<JsonValue, String> {
+        let inputs = RcIter::new(core::iter::empty());
+        let outputs = self.filter.run((Ctx::new([], &inputs), Val::from(value)));
+
+        let mut results = Vec::new();
+        for output in outputs {
+            match output {
+                Ok(val) => results.push(JsonValue::from(val)),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        Ok(match results.len() {
+            0 => JsonValue::Null,
+            1 => results.pop().unwrap(),
+            _ => JsonValue::Array(results),
+        })
+    }
+
+    /// 对一行对象求值，并转换为表格可展示的对象
+    ///
+    /// 非对象的结果包装为 `{"value": ...}`。
+    pub fn apply_to_object(&self, obj: JsonObject) -> Result<JsonObject, String> {
+        let value = JsonValue::Object(obj.into_iter().collect());
+        match self.apply(value)? {
+            JsonValue::Object(map) => Ok(map.into_iter().collect()),
+            other => {
+                let mut wrapped = JsonObject::new();
+                wrapped.insert("value".to_string(), other);
+                Ok(wrapped)
+            }
+        }
+    }
+}
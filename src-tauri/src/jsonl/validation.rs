@@ -0,0 +1,125 @@
+use crate::jsonl::MappedLines;
+use crate::models::{AppError, AppResult, JsonValue, LoadingProgress, SchemaViolation};
+use jsonschema::{Draft, Validator};
+
+/// 每批并行处理的行数，每批结束后报告一次进度
+const BATCH_LINES: usize = 50_000;
+
+/// 每行最多记录的违规数量
+const MAX_ERRORS_PER_ROW: usize = 20;
+
+/// 编译后的 JSON Schema
+pub struct RowValidator {
+    validator: Validator,
+}
+
+impl RowValidator {
+    /// 从文件加载 JSON Schema
+    ///
+    /// `draft` 为 `draft7` 或 `2020-12`，为空时根据 `$schema` 自动识别，缺省按 2020-12 处理。
+    pub fn load(schema_path: &str, draft: Option<&str>) -> AppResult<Self> {
+        let text = std::fs::read_to_string(schema_path)?;
+        let schema: JsonValue = serde_json::from_str(&text)
+            .map_err(|e| AppError::General(format!("JSON Schema 解析错误: {}", e)))?;
+
+        let mut options = jsonschema::options();
+        match draft {
+            None | Some("") | Some("auto") => {}
+            Some("draft7") | Some("7") => options = options.with_draft(Draft::Draft7),
+            Some("2020-12") | Some("draft2020-12") => options = options.with_draft(Draft::Draft202012),
+            Some(other) => return Err(AppError::General(format!("不支持的 JSON Schema 版本: {}", other))),
+        }
+
+        let validator = options
+            .build(&schema)
+            .map_err(|e| AppError::General(format!("无效的 JSON Schema: {}", e)))?;
+        Ok(Self { validator })
+    }
+
+    /// 校验一行，返回违规列表，合法的行返回空列表
+    fn check(&self, line_num: usize, line: &str) -> Vec<SchemaViolation> {
+        let row = match serde_json::from_str::<JsonValue>(line) {
+            Ok(row) => row,
+            Err(e) => {
+                return vec![SchemaViolation {
+                    line: line_num,
+                    instance_path: String::new(),
+                    schema_path: String::new(),
+                    message: format!("JSON 解析错误: {}", e),
+                }]
+            }
+        };
+
+        self.validator
+            .iter_errors(&row)
+            .take(MAX_ERRORS_PER_ROW)
+            .map(|error| SchemaViolation {
+                line: line_num,
+                instance_path: error.instance_path().to_string(),
+                schema_path: error.schema_path().to_string(),
+                message: error.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// 校验结果
+pub struct SchemaValidation {
+    /// 文件路径
+    pub path: String,
+
+    /// Schema 文件路径
+    pub schema_path: String,
+
+    /// 参与校验的非空行数
+    pub checked_lines: usize,
+
+    /// 不合法的行号，升序
+    pub invalid_lines: Vec<usize>,
+
+    /// 全部违规，按行号升序
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl SchemaValidation {
+    /// 并行校验文件的每一行
+    pub fn run<F>(path: &str, line_offsets: &[u64], schema_path: &str, validator: &RowValidator, progress_callback: F) -> AppResult<Self>
+    where
+        F: Fn(LoadingProgress),
+    {
+        let lines = MappedLines::open(path, line_offsets)?;
+        let total = lines.len();
+
+        let mut checked_lines = 0;
+        let mut invalid_lines = Vec::new();
+        let mut violations = Vec::new();
+        let mut start = 0;
+        while start < total {
+            let end = (start + BATCH_LINES).min(total);
+            let batch = lines.par_filter_map_range(start..end, |line_num, line| Some(validator.check(line_num, line)));
+            checked_lines += batch.len();
+            for row_violations in batch {
+                if let Some(first) = row_violations.first() {
+                    invalid_lines.push(first.line);
+                    violations.extend(row_violations);
+                }
+            }
+            start = end;
+
+            progress_callback(LoadingProgress {
+                current: start,
+                total,
+                stage: "校验 JSON Schema".to_string(),
+                percentage: start as f32 / total as f32 * 100.0,
+            });
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            schema_path: schema_path.to_string(),
+            checked_lines,
+            invalid_lines,
+            violations,
+        })
+    }
+}
@@ -7,6 +7,7 @@ pub mod commands;
 
 use jsonl::{JsonlReaderManager, DebugDataManager};
 use commands::*;
+use tauri::Manager;
 
 // Tauri 应用程序入口点
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -24,13 +25,70 @@ pub fn run() {
         .manage(jsonl_manager)
         .manage(debug_manager)
         
+        // 初始化应用目录
+        .setup(|app| {
+            let index_dir = app.path().app_cache_dir()?.join("indexes");
+            app.state::<JsonlReaderManager>().set_index_dir(index_dir);
+            let draft_dir = app.path().app_data_dir()?.join("drafts");
+            app.state::<JsonlReaderManager>().set_draft_dir(draft_dir);
+            Ok(())
+        })
+        
         // 注册命令处理器
         .invoke_handler(tauri::generate_handler![
             // JSONL 文件处理命令
             set_jsonl_file,
             load_page,
+            load_flat_page,
             search_page,
+            cancel_search,
+            fuzzy_search_page,
             get_total_lines,
+            export_jsonl,
+            build_search_index,
+            get_index_status,
+            drop_search_index,
+            sort_by_field,
+            clear_sort,
+            get_sort_status,
+            analyze_duplicates,
+            get_duplicate_groups,
+            export_deduplicated,
+            analyze_near_duplicates,
+            get_near_duplicate_clusters,
+            infer_schema,
+            export_inferred_schema,
+            get_field_statistics,
+            get_chart_histogram,
+            get_chart_categories,
+            get_chart_time_series,
+            get_chart_scatter,
+            validate_with_schema,
+            get_schema_violations,
+            load_invalid_page,
+            get_quality_report,
+            load_quality_issue_page,
+            diff_files,
+            get_diff_page,
+            diff_rows,
+            set_field_value,
+            add_field,
+            remove_field,
+            rename_field,
+            insert_rows,
+            delete_rows,
+            duplicate_rows,
+            get_edit_status,
+            get_edit_history,
+            undo_edit,
+            redo_edit,
+            discard_edits,
+            save_edits,
+            save_edits_as,
+            rebase_edits,
+            get_draft_status,
+            restore_draft,
+            discard_draft,
             
             // 调试数据命令
             init_debug_data,
@@ -39,6 +97,12 @@ pub fn run() {
             get_debug_total_rows,
             generate_test_jsonl,
         ])
-        .run(tauri::generate_context!())
-        .expect("Tauri 应用运行失败");
+        .build(tauri::generate_context!())
+        .expect("Tauri 应用构建失败")
+        .run(|app, event| {
+            // 退出前写入尚未写入的编辑草稿
+            if let tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit = event {
+                app.state::<JsonlReaderManager>().flush_pending_draft();
+            }
+        });
 }
@@ -8,13 +8,76 @@ pub type JsonValue = serde_json::Value;
 pub type JsonObject = HashMap<String, JsonValue>;
 
 /// 分页响应数据
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PagedResponse {
     /// 当前页数据
     pub data: Vec<JsonObject>,
     
     /// 总行数
     pub total: usize,
+    
+    /// 每条数据在文件中的行号（从0开始），与 `data` 一一对应
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<usize>,
+    
+    /// 变换失败的行，`index` 对应 `data` 中的位置
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<RowError>,
+    
+    /// 搜索是否已扫描完整个文件，未完成时 `total` 为当前已找到的匹配数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complete: Option<bool>,
+    
+    /// 搜索命中位置，与 `data` 一一对应
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<Vec<MatchLocation>>>,
+    
+    /// 模糊搜索的匹配得分（0-1），与 `data` 一一对应
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Vec<f32>>,
+    
+    /// 展开嵌套字段后的列名，按固定顺序排列，只在展开视图中返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<String>>,
+}
+
+/// 单个字符串值中的搜索命中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchLocation {
+    /// 值所在的字段路径，例如 `messages[0].content`
+    pub path: String,
+    
+    /// 命中的字符区间 `[start, end)`，按 Unicode 字符计数
+    pub ranges: Vec<[usize; 2]>,
+}
+
+/// 单行处理错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowError {
+    /// 在当前页数据中的位置
+    pub index: usize,
+    
+    /// 文件中的行号，从0开始
+    pub line: usize,
+    
+    /// 错误信息
+    pub message: String,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    /// 输出文件路径
+    pub path: String,
+    
+    /// 写入的行数
+    pub written: usize,
+    
+    /// 因变换失败而跳过的行数
+    pub skipped: usize,
+    
+    /// 部分失败行的错误信息
+    pub errors: Vec<RowError>,
 }
 
 /// 分页请求参数
@@ -54,4 +117,711 @@ pub struct LoadingProgress {
     
     /// 进度百分比 (0-100)
     pub percentage: f32,
-} 
\ No newline at end of file
+}
+
+/// 搜索进度通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchProgress {
+    /// 搜索会话编号，用于丢弃过期的进度事件
+    pub search_id: u64,
+    
+    /// 搜索关键词
+    pub keyword: String,
+    
+    /// 已扫描的字节数
+    pub bytes_scanned: u64,
+    
+    /// 文件总字节数
+    pub total_bytes: u64,
+    
+    /// 目前找到的匹配行数
+    pub matches: usize,
+    
+    /// 进度百分比 (0-100)
+    pub percentage: f32,
+    
+    /// 是否已结束（完成或取消）
+    pub done: bool,
+    
+    /// 是否被取消
+    pub cancelled: bool,
+}
+
+/// 搜索索引状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStatus {
+    /// 索引是否可用
+    pub ready: bool,
+    
+    /// 是否正在后台构建
+    pub building: bool,
+    
+    /// 索引文件路径
+    pub index_path: Option<String>,
+    
+    /// 索引覆盖的行数
+    pub total_lines: usize,
+    
+    /// 不同词元的数量
+    pub token_count: usize,
+}
+
+/// 排序状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortStatus {
+    /// 排序字段路径
+    pub key_path: String,
+    
+    /// 是否降序
+    pub descending: bool,
+    
+    /// 参与排序的行数
+    pub total: usize,
+    
+    /// 排序后是否又有编辑，此时行的顺序可能与编辑后的值不一致，重新排序即可更新
+    pub stale: bool,
+}
+
+/// 重复检测摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupSummary {
+    /// 判定方式：exact / canonical / keys
+    pub mode: String,
+    
+    /// 参与比较的行数
+    pub compared_lines: usize,
+    
+    /// 重复组数量
+    pub duplicate_groups: usize,
+    
+    /// 去重后会被删除的行数
+    pub redundant_rows: usize,
+}
+
+/// 一组重复行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// 组内行号（从0开始，升序），过大的组只返回前一部分
+    pub lines: Vec<usize>,
+    
+    /// 组内行数
+    pub count: usize,
+    
+    /// 组内第一行的数据
+    pub sample: Option<JsonObject>,
+}
+
+/// 重复组分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroupPage {
+    /// 当前页的重复组
+    pub groups: Vec<DuplicateGroup>,
+    
+    /// 重复组总数
+    pub total: usize,
+}
+
+/// 近似重复检测摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearDupSummary {
+    /// 参与比较的字段路径
+    pub field: String,
+    
+    /// 相似度阈值 (0-1)
+    pub threshold: f32,
+    
+    /// 参与比较的行数（字段存在且包含文本）
+    pub compared_lines: usize,
+    
+    /// 近似重复簇数量
+    pub clusters: usize,
+    
+    /// 属于某个簇的行数
+    pub clustered_rows: usize,
+}
+
+/// 字段的一种观测类型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldType {
+    /// 类型名：string / integer / number / boolean / null / object / array
+    pub name: String,
+    
+    /// 出现次数
+    pub count: usize,
+}
+
+/// 单个字段路径的推断结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSchema {
+    /// 字段路径，数组元素记作 `[]`，例如 `messages[].content`
+    pub path: String,
+    
+    /// 观测到的类型，按出现次数降序
+    pub types: Vec<FieldType>,
+    
+    /// 是否为联合类型（除 null 外出现了多种类型，整数与小数视为同一种）
+    pub union: bool,
+    
+    /// 出现次数（数组内的字段每个元素计一次）
+    pub occurrences: usize,
+    
+    /// 含有该字段的行数
+    pub present_rows: usize,
+    
+    /// 含有该字段的行占比 (0-1)
+    pub presence_ratio: f32,
+    
+    /// 值为 null 的占比 (0-1)
+    pub null_ratio: f32,
+    
+    /// 示例值，过长的字符串会被截断
+    pub examples: Vec<JsonValue>,
+    
+    /// 全部不同的字符串值（升序），不超过 50 个且都较短时才列出
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distinct_strings: Option<Vec<String>>,
+    
+    /// 数组的最大长度，值不是数组时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+}
+
+/// 结构推断结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaReport {
+    /// 文件路径
+    pub path: String,
+    
+    /// 文件总行数
+    pub total_lines: usize,
+    
+    /// 参与推断的有效行数
+    pub rows_scanned: usize,
+    
+    /// 无法解析或不是对象的行数
+    pub invalid_rows: usize,
+    
+    /// 是否为抽样结果
+    pub sampled: bool,
+    
+    /// 请求的抽样行数
+    pub sample_size: Option<usize>,
+    
+    /// 顶层字段名，按出现行数降序，可直接作为表格列
+    pub columns: Vec<String>,
+    
+    /// 全部字段路径，按路径排序
+    pub fields: Vec<FieldSchema>,
+}
+
+/// 分位数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quantile {
+    /// 分位点 (0-1)
+    pub q: f64,
+    
+    /// 近似值，相对误差不超过 1%
+    pub value: f64,
+}
+
+/// 数值摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumericSummary {
+    /// 数值个数
+    pub count: usize,
+    
+    /// 最小值
+    pub min: f64,
+    
+    /// 最大值
+    pub max: f64,
+    
+    /// 平均值
+    pub mean: f64,
+    
+    /// 总体标准差
+    pub std_dev: f64,
+    
+    /// 近似分位数
+    pub quantiles: Vec<Quantile>,
+}
+
+/// 直方图中的一个区间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// 区间起点（含）
+    pub start: f64,
+    
+    /// 区间终点（不含）
+    pub end: f64,
+    
+    /// 落在区间内的数量
+    pub count: usize,
+}
+
+/// 字符串长度分布
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LengthSummary {
+    /// 长度（按字符计数）的数值摘要
+    pub summary: NumericSummary,
+    
+    /// 按 2 的幂划分的长度直方图，省略空区间
+    pub histogram: Vec<HistogramBucket>,
+}
+
+/// 值及其出现次数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueCount {
+    /// 值，过长的字符串会被截断
+    pub value: JsonValue,
+    
+    /// 出现次数
+    pub count: usize,
+}
+
+/// 单个字段的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldStatistics {
+    /// 字段路径
+    pub path: String,
+    
+    /// 含有该字段的行数
+    pub present: usize,
+    
+    /// 缺少该字段的行数
+    pub missing: usize,
+    
+    /// 值为 null 的行数
+    pub nulls: usize,
+    
+    /// 值的类型分布
+    pub types: Vec<FieldType>,
+    
+    /// 数值字段的摘要
+    pub numeric: Option<NumericSummary>,
+    
+    /// 字符串字段的长度分布
+    pub length: Option<LengthSummary>,
+    
+    /// 出现次数最多的值，按次数降序
+    pub top_values: Vec<ValueCount>,
+    
+    /// 高频值计数是否精确，不同值过多时为下界
+    pub top_values_exact: bool,
+    
+    /// 不同值数量的估计（误差约 1%）
+    pub distinct_estimate: usize,
+}
+
+/// 字段统计结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldStatsReport {
+    /// 过滤关键词，为空表示整个文件
+    pub keyword: Option<String>,
+    
+    /// 参与统计的有效行数
+    pub rows: usize,
+    
+    /// 各字段的统计
+    pub fields: Vec<FieldStatistics>,
+}
+
+/// 数值字段的直方图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramData {
+    /// 字段路径
+    pub field: String,
+    
+    /// 参与统计的数值个数
+    pub count: usize,
+    
+    /// 字段缺失或不是数值的行数
+    pub missing: usize,
+    
+    /// 等宽区间，最后一个区间包含最大值
+    pub buckets: Vec<HistogramBucket>,
+}
+
+/// 类别字段的计数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryData {
+    /// 字段路径
+    pub field: String,
+    
+    /// 出现次数最多的类别，按次数降序
+    pub categories: Vec<ValueCount>,
+    
+    /// 其余类别的行数合计
+    pub other: usize,
+    
+    /// 缺少该字段的行数
+    pub missing: usize,
+    
+    /// 计数是否精确，不同值过多时为下界
+    pub exact: bool,
+}
+
+/// 时间序列中的一个点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSeriesPoint {
+    /// 桶标签：按天、周为 `YYYY-MM-DD`（周为周一），按月为 `YYYY-MM`
+    pub bucket: String,
+    
+    /// 桶的起始时间（UTC，Unix 毫秒）
+    pub start: i64,
+    
+    /// 行数
+    pub count: usize,
+}
+
+/// 日期字段的时间序列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSeriesData {
+    /// 字段路径
+    pub field: String,
+    
+    /// 分桶粒度：day / week / month
+    pub interval: String,
+    
+    /// 按时间升序的点，只包含有数据的桶
+    pub points: Vec<TimeSeriesPoint>,
+    
+    /// 字段存在但无法解析为日期的行数
+    pub unparsed: usize,
+    
+    /// 缺少该字段的行数
+    pub missing: usize,
+    
+    /// 桶数量超过上限，只返回了最早的一部分
+    pub truncated: bool,
+}
+
+/// 散点图中的一个点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScatterPoint {
+    /// 文件中的行号，从0开始
+    pub line: usize,
+    
+    /// 横坐标
+    pub x: f64,
+    
+    /// 纵坐标
+    pub y: f64,
+}
+
+/// 两个数值字段的散点抽样
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScatterData {
+    /// 横坐标字段路径
+    pub x_field: String,
+    
+    /// 纵坐标字段路径
+    pub y_field: String,
+    
+    /// 两个字段均为数值的行数
+    pub total: usize,
+    
+    /// 抽样的点，按行号升序
+    pub points: Vec<ScatterPoint>,
+}
+
+/// 一条 JSON Schema 违规
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// 文件中的行号，从0开始
+    pub line: usize,
+    
+    /// 违规值在行内的位置（JSON Pointer），整行为空字符串
+    pub instance_path: String,
+    
+    /// 触发违规的 Schema 关键字位置（JSON Pointer）
+    pub schema_path: String,
+    
+    /// 错误信息
+    pub message: String,
+}
+
+/// JSON Schema 校验摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    /// Schema 文件路径
+    pub schema_path: String,
+    
+    /// 参与校验的非空行数
+    pub checked_lines: usize,
+    
+    /// 不合法的行数
+    pub invalid_rows: usize,
+    
+    /// 违规总数（每行最多记录 20 条）
+    pub violations: usize,
+}
+
+/// 违规分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViolationPage {
+    /// 当前页的违规
+    pub violations: Vec<SchemaViolation>,
+    
+    /// 违规总数
+    pub total: usize,
+}
+
+/// 一类数据质量问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityIssue {
+    /// 问题类型：unparseable / missing_field / empty_string / type_mismatch / too_long / too_short / repeated_value
+    pub kind: String,
+    
+    /// 字段路径，数组元素记作 `[]`；整行问题为空字符串
+    pub path: String,
+    
+    /// 涉及的行数
+    pub count: usize,
+    
+    /// 判定依据的说明
+    pub detail: String,
+    
+    /// 前若干个涉及的行号
+    pub sample_lines: Vec<usize>,
+}
+
+/// 数据质量报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityReport {
+    /// 文件总行数
+    pub total_lines: usize,
+    
+    /// 可解析为对象的行数
+    pub rows: usize,
+    
+    /// 发现的问题，按类型、涉及行数降序排列
+    pub issues: Vec<QualityIssue>,
+}
+
+/// 两个文件的比较摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSummary {
+    /// 左侧（旧）文件路径
+    pub left_path: String,
+    
+    /// 右侧（新）文件路径
+    pub right_path: String,
+    
+    /// 按字段值对齐时的字段路径，按行号对齐时为空
+    pub key_field: Option<String>,
+    
+    /// 左侧文件的非空行数
+    pub left_rows: usize,
+    
+    /// 右侧文件的非空行数
+    pub right_rows: usize,
+    
+    /// 内容相同的行数
+    pub unchanged: usize,
+    
+    /// 只在右侧出现的行数
+    pub added: usize,
+    
+    /// 只在左侧出现的行数
+    pub removed: usize,
+    
+    /// 两侧都有但内容不同的行数
+    pub changed: usize,
+    
+    /// 左侧缺少对齐字段或无法解析、未参与比较的行数
+    pub left_missing_key: usize,
+    
+    /// 右侧缺少对齐字段或无法解析、未参与比较的行数
+    pub right_missing_key: usize,
+    
+    /// 对齐字段值与同侧前面的行重复的行数，重复值按出现顺序依次配对
+    pub duplicate_keys: usize,
+}
+
+/// 字段级差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// 字段路径，例如 `address.city`、`tags[2]`；整行被替换时为空字符串
+    pub path: String,
+    
+    /// 差异类型：added / removed / changed
+    pub kind: String,
+    
+    /// 左侧的值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub left: Option<JsonValue>,
+    
+    /// 右侧的值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right: Option<JsonValue>,
+    
+    /// 较长字符串的字符级差异，只在比较单行时计算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_diff: Option<Vec<TextSegment>>,
+}
+
+/// 字符级差异中的一段文本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSegment {
+    /// 操作：equal / insert / delete
+    pub op: String,
+    
+    /// 文本内容
+    pub text: String,
+}
+
+/// 行级差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowDiff {
+    /// 差异类型：added / removed / changed
+    pub kind: String,
+    
+    /// 左侧行号，新增的行为空
+    pub left_line: Option<usize>,
+    
+    /// 右侧行号，删除的行为空
+    pub right_line: Option<usize>,
+    
+    /// 对齐字段的值，按行号对齐时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<JsonValue>,
+    
+    /// 新增或删除的整行内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<JsonValue>,
+    
+    /// 内容不同的行的字段差异
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<FieldChange>,
+}
+
+/// 分页的行级差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffPage {
+    /// 当前页的差异
+    pub entries: Vec<RowDiff>,
+    
+    /// 符合筛选条件的差异总数
+    pub total: usize,
+}
+
+/// 行引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowRef {
+    /// 文件路径，为空时指当前打开的文件
+    #[serde(default)]
+    pub path: Option<String>,
+    
+    /// 行号（从0开始）
+    pub line: usize,
+}
+
+/// 两行的比较结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowComparison {
+    /// 左侧行内容，无法解析为 JSON 时为原始文本
+    pub left: JsonValue,
+    
+    /// 右侧行内容，无法解析为 JSON 时为原始文本
+    pub right: JsonValue,
+    
+    /// 两行内容是否相同（忽略字段顺序与空白）
+    pub identical: bool,
+    
+    /// 字段级差异，按路径排列
+    pub changes: Vec<FieldChange>,
+}
+
+/// 尚未保存的编辑状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditStatus {
+    /// 待保存的编辑操作数
+    pub pending_edits: usize,
+    
+    /// 被单行编辑修改过的行数
+    pub edited_rows: usize,
+    
+    /// 作用于多行的批量操作数
+    pub bulk_edits: usize,
+    
+    /// 新插入（包括复制）的行数
+    pub inserted_rows: usize,
+    
+    /// 原文件中被删除的行数
+    pub deleted_rows: usize,
+    
+    /// 应用增删行后的总行数
+    pub total_rows: usize,
+    
+    /// 是否有可以撤销的操作
+    pub can_undo: bool,
+    
+    /// 是否有可以重做的操作
+    pub can_redo: bool,
+}
+
+/// 编辑历史中的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditHistoryEntry {
+    /// 操作类型，例如 `set_value`、`rename_field`、`delete_rows`
+    pub kind: String,
+    
+    /// 操作的简要说明
+    pub description: String,
+    
+    /// 是否已被撤销（可以重做）
+    pub undone: bool,
+}
+
+/// 编辑历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditHistory {
+    /// 已执行的操作按执行顺序排列，之后是已撤销的操作按重做顺序排列
+    pub entries: Vec<EditHistoryEntry>,
+    
+    /// 当前的编辑状态
+    pub status: EditStatus,
+}
+
+/// 保存编辑的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveSummary {
+    /// 保存到的文件路径
+    pub path: String,
+    
+    /// 写入的行数
+    pub rows: usize,
+    
+    /// 原文件的备份路径，未备份时为空
+    pub backup_path: Option<String>,
+    
+    /// 编辑已写入但重新打开文件失败时的错误信息，成功时为空
+    pub reopen_error: Option<String>,
+}
+
+/// 把编辑移植到文件新内容的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseSummary {
+    /// 保留的编辑操作数
+    pub kept_edits: usize,
+    
+    /// 无法对应到新内容而被丢弃的编辑操作数
+    pub dropped_edits: usize,
+    
+    /// 移植后的编辑状态
+    pub status: EditStatus,
+}
+
+/// 当前文件的草稿状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DraftStatus {
+    /// 草稿保存时间（Unix 毫秒）
+    pub saved_at_ms: u64,
+    
+    /// 草稿中的编辑操作数
+    pub pending_edits: usize,
+    
+    /// 文件在草稿保存后是否被修改过，被修改时不能恢复
+    pub file_changed: bool,
+}
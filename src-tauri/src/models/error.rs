@@ -17,9 +17,15 @@ pub enum AppError {
     #[error("页码超出范围")]
     PageOutOfRange,
 
+    #[error("变换程序错误: {0}")]
+    Transform(String),
+
     #[error("调试数据生成错误: {0}")]
     DebugError(String),
 
+    #[error("保存冲突: {0}")]
+    SaveConflict(String),
+
     #[error("一般错误: {0}")]
     General(String),
 }